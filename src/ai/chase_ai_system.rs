@@ -0,0 +1,44 @@
+use specs::prelude::*;
+use crate::{MyTurn, Map, Position, Chasing, RemembersPlayer, WantsToApproach};
+
+/// Sends anyone who has lost sight of their quarry to where they last saw it, for as long as they
+/// remember it. If they get there and find nothing, or their quarry dies, they give up the chase.
+pub struct ChaseAI {}
+
+impl<'a> System<'a> for ChaseAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Chasing>,
+        WriteStorage<'a, RemembersPlayer>,
+        WriteStorage<'a, WantsToApproach>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (map, entities, mut turns, positions, mut chasing, mut remembers_player, mut wants_approach) = data;
+
+        let mut turn_done: Vec<Entity> = Vec::new();
+        let mut lost_trail: Vec<Entity> = Vec::new();
+        for (entity, _turn, pos, chase, remembers) in (&entities, &turns, &positions, &chasing, &mut remembers_player).join() {
+            let arrived = pos.x == chase.last_seen.x && pos.y == chase.last_seen.y;
+            if remembers.memory < 1 || arrived || !entities.is_alive(chase.target) {
+                lost_trail.push(entity);
+                continue;
+            }
+            remembers.memory -= 1;
+            let idx = map.xy_idx(chase.last_seen.x, chase.last_seen.y);
+            wants_approach.insert(entity, WantsToApproach{ idx }).expect("Unable to insert intent");
+            turn_done.push(entity);
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+        for lost in lost_trail.iter() {
+            chasing.remove(*lost);
+        }
+    }
+}
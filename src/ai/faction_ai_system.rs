@@ -0,0 +1,69 @@
+use specs::prelude::*;
+use crate::{
+    Viewshed, Position, Map, MyTurn, Faction, Grudges, Reaction, WantsToReact,
+    raws::{RAWS, faction_reaction, reaction_to_player},
+};
+use rltk::{Point};
+
+/// Looks over everything each creature can see and decides how it feels about the most pressing
+/// of them, using the reaction table in the raws. Threats (things to attack or run from) come
+/// first, nearest first; failing those, a creature tags along after the nearest thing it follows.
+/// The later stages of the AI act on the decision.
+pub struct FactionAI {}
+
+impl<'a> System<'a> for FactionAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        Entities<'a>,
+        ReadStorage<'a, MyTurn>,
+        ReadStorage<'a, Viewshed>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Grudges>,
+        WriteStorage<'a, WantsToReact>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (map, player_entity, entities, turns, viewsheds, positions, factions, grudges, mut wants_react) = data;
+
+        wants_react.clear();
+        let raws = RAWS.lock().unwrap();
+
+        for (entity, _turn, viewshed, pos, faction) in (&entities, &turns, &viewsheds, &positions, &factions).join() {
+            let my_pos = Point::new(pos.x, pos.y);
+            let mut chosen: Option<(Reaction, Entity, Point, f32)> = None;
+
+            for tile in viewshed.visible_tiles.iter() {
+                let idx = map.xy_idx(tile.x, tile.y);
+                for other in map.tile_content[idx].iter() {
+                    if *other == entity { continue; }
+                    let reaction = match factions.get(*other) {
+                        None => continue,
+                        Some(_) if *other == *player_entity => reaction_to_player(&faction.name, grudges.get(*other), &raws),
+                        Some(theirs) => faction_reaction(&faction.name, &theirs.name, &raws),
+                    };
+                    if reaction == Reaction::Ignore { continue; }
+
+                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *tile);
+                    let threat = reaction != Reaction::Follow;
+                    let better = match chosen {
+                        None => true,
+                        Some((best, _, _, best_distance)) => {
+                            let best_threat = best != Reaction::Follow;
+                            (threat && !best_threat) || (threat == best_threat && distance < best_distance)
+                        }
+                    };
+                    if better {
+                        chosen = Some((reaction, *other, *tile, distance));
+                    }
+                }
+            }
+
+            if let Some((reaction, target, at, _distance)) = chosen {
+                wants_react.insert(entity, WantsToReact{ reaction, target, at }).expect("Unable to insert intent");
+            }
+        }
+    }
+}
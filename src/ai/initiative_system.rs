@@ -0,0 +1,48 @@
+use specs::prelude::*;
+use crate::{
+    MyTurn, RunState, Monster, Bystander, Vendor, Herbivore, Carnivore, Awareness, Alertness, SpecialAbilities,
+};
+
+/// Hands out a turn to everything with an AI, at the start of the monster turn. Monsters who
+/// haven't noticed the player yet stay where they are. Vendors get a turn too, so they can react
+/// to trouble, but only bystanders wander about.
+pub struct InitiativeSystem {}
+
+impl<'a> System<'a> for InitiativeSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Bystander>,
+        ReadStorage<'a, Vendor>,
+        ReadStorage<'a, Herbivore>,
+        ReadStorage<'a, Carnivore>,
+        ReadStorage<'a, Awareness>,
+        WriteStorage<'a, SpecialAbilities>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, runstate, mut turns, monsters, bystanders, vendors, herbivores, carnivores, awareness, mut special_abilities) = data;
+
+        if *runstate != RunState::MonsterTurn { return; }
+
+        turns.clear();
+
+        // Abilities recharge whatever their owner is up to
+        for special in (&mut special_abilities).join() {
+            for ability in special.abilities.iter_mut() {
+                ability.ready_in = i32::max(0, ability.ready_in - 1);
+            }
+        }
+
+        for entity in entities.join() {
+            let has_ai = monsters.get(entity).is_some() || bystanders.get(entity).is_some() || vendors.get(entity).is_some()
+                || herbivores.get(entity).is_some() || carnivores.get(entity).is_some();
+            if !has_ai { continue; }
+            if awareness.get(entity).is_some_and(|a| a.state != Alertness::Alert) { continue; }
+            turns.insert(entity, MyTurn{}).expect("Unable to insert turn");
+        }
+    }
+}
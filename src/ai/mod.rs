@@ -0,0 +1,24 @@
+//! The AI runs as a pipeline of stages, one system each, over everything holding a `MyTurn`:
+//! the initiative stage hands out turns, the status stage takes them from the stunned and the
+//! confused, the decision stages turn what each creature can see (and remembers) into attacks or
+//! movement intents, and the movement system carries those out. A new kind of creature is a new
+//! combination of the components these stages look for, rather than a new system.
+
+mod initiative_system;
+pub use initiative_system::InitiativeSystem;
+mod turn_status_system;
+pub use turn_status_system::TurnStatusSystem;
+mod faction_ai_system;
+pub use faction_ai_system::FactionAI;
+mod quip_system;
+pub use quip_system::QuipSystem;
+mod morale_ai_system;
+pub use morale_ai_system::MoraleAI;
+mod reaction_ai_system;
+pub use reaction_ai_system::ReactionAI;
+mod chase_ai_system;
+pub use chase_ai_system::ChaseAI;
+mod wander_ai_system;
+pub use wander_ai_system::WanderAI;
+mod movement_system;
+pub use movement_system::MovementSystem;
@@ -0,0 +1,144 @@
+use specs::prelude::*;
+use crate::{
+    MyTurn, Map, Position, Viewshed, Morale, MoraleBreak, Name, gamelog::GameLog, WantsToReact,
+    WantsToMelee, WantsToFlee, Monster, Bystander, Equipped, EquipmentSlot, RemembersPlayer,
+    Awareness, Alertness, Chasing, Faction, status_effect_system::flee_destination,
+};
+use rltk::{Point};
+
+/// Checks whether each monster's nerve holds. Out of sight of the player it slowly returns, and a
+/// broken monster rallies once it's got half of it back. Until then it runs from whatever it was
+/// fighting, only standing its ground when it's cornered.
+pub struct MoraleAI {}
+
+impl<'a> System<'a> for MoraleAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        ReadExpect<'a, Entity>,
+        Entities<'a>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Viewshed>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Morale>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, WantsToReact>,
+        WriteStorage<'a, WantsToMelee>,
+        WriteStorage<'a, WantsToFlee>,
+        WriteStorage<'a, Monster>,
+        WriteStorage<'a, Bystander>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, RemembersPlayer>,
+        WriteStorage<'a, Awareness>,
+        WriteStorage<'a, Chasing>,
+        WriteStorage<'a, Faction>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut map, player_pos, player_entity, entities, mut turns, viewsheds, mut positions,
+            mut morale, names, mut gamelog, wants_react, mut wants_to_melee, mut wants_flee,
+            mut monsters, mut bystanders, mut equipped, mut remembers_player, mut awareness, mut chasing, mut factions,
+        ) = data;
+
+        let mut turn_done: Vec<Entity> = Vec::new();
+        // Monsters giving up the fight, and the places monsters have cried out for help from (and
+        // who they want help with)
+        let mut surrendered: Vec<Entity> = Vec::new();
+        let mut calls_for_help: Vec<(Point, Entity)> = Vec::new();
+
+        for (entity, _turn, viewshed, pos, nerve) in (&entities, &turns, &viewsheds, &positions, &mut morale).join() {
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            let name = names.get(entity).map_or("Something".to_string(), |n| n.name.clone());
+            let (foe, foe_pos) = match wants_react.get(entity) {
+                Some(decision) => (decision.target, decision.at),
+                None => (*player_entity, *player_pos),
+            };
+
+            if !viewshed.visible_tiles.contains(&*player_pos) {
+                nerve.current = i32::min(nerve.max, nerve.current + 1);
+            }
+            if nerve.broken && nerve.current * 2 >= nerve.max {
+                nerve.broken = false;
+                if map.visible_tiles[my_idx] {
+                    gamelog.entries.push(format!("{} rallies!", name));
+                }
+            } else if !nerve.broken && nerve.current <= 0 {
+                nerve.broken = true;
+                match nerve.on_break {
+                    MoraleBreak::Flee => {
+                        if map.visible_tiles[my_idx] {
+                            gamelog.entries.push(format!("{} turns to flee!", name));
+                        }
+                    }
+                    MoraleBreak::Surrender => {
+                        if map.visible_tiles[my_idx] {
+                            gamelog.entries.push(format!("{} throws down their weapon and begs for mercy!", name));
+                        }
+                        surrendered.push(entity);
+                    }
+                    MoraleBreak::CallForHelp => {
+                        if map.visible_tiles[my_idx] {
+                            gamelog.entries.push(format!("{} cries out for help!", name));
+                        }
+                        calls_for_help.push((Point::new(pos.x, pos.y), foe));
+                    }
+                }
+            }
+            if !nerve.broken { continue; }
+
+            // Running away, unless they're cornered and have to fight
+            let foe_idx = map.xy_idx(foe_pos.x, foe_pos.y);
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), foe_pos);
+            match flee_destination(&mut map, my_idx, &[foe_idx]) {
+                Some(_) => {
+                    wants_flee.insert(entity, WantsToFlee{ indices: vec![foe_idx] }).expect("Unable to insert intent");
+                }
+                None if distance < 1.5 && !surrendered.contains(&entity) => {
+                    wants_to_melee.insert(entity, WantsToMelee{ target: foe }).expect("Unable to insert attack.");
+                }
+                None => {}
+            }
+            turn_done.push(entity);
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+
+        // Surrendering monsters drop their weapons and stop fighting, for good
+        for entity in surrendered.iter() {
+            let at = positions.get(*entity).cloned();
+            let weapons: Vec<Entity> = (&entities, &equipped).join()
+                .filter(|(_item, worn)| worn.owner == *entity && worn.slot == EquipmentSlot::Melee)
+                .map(|(item, _worn)| item)
+                .collect();
+            for weapon in weapons.iter() {
+                equipped.remove(*weapon);
+                if let Some(at) = &at {
+                    positions.insert(*weapon, at.clone()).expect("Unable to insert position");
+                }
+            }
+            monsters.remove(*entity);
+            bystanders.insert(*entity, Bystander{}).expect("Unable to insert bystander");
+            factions.insert(*entity, Faction{ name: "Surrendered".to_string() }).expect("Unable to insert faction");
+            morale.remove(*entity);
+            chasing.remove(*entity);
+        }
+
+        // Calls for help bring every monster in earshot running
+        for (shout, foe) in calls_for_help.iter() {
+            for (entity, pos, remembers) in (&entities, &positions, &mut remembers_player).join() {
+                if rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *shout) <= 15.0 {
+                    remembers.memory = i32::max(remembers.max_memory, remembers.memory) + 10;
+                    chasing.insert(entity, Chasing{ target: *foe, last_seen: *shout }).expect("Unable to insert chase");
+                    if let Some(aware) = awareness.get_mut(entity) {
+                        aware.state = Alertness::Alert;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use specs::prelude::*;
+use crate::{
+    MyTurn, Map, Position, Viewshed, WantsToApproach, WantsToFlee, EntityMoved, StatusEffects,
+    StatusEffect, Monster, Herbivore, Carnivore, Door, BlocksTile, BlocksVisibility, Renderable, Name,
+    gamelog::GameLog, doors::set_door_open, status_effect_system::{flee_destination, struggle},
+};
+use rltk::{Point};
+
+/// Moves someone onto `dest`, if it's free, keeping the map's blocked tiles up to date. On ice
+/// they keep sliding the way they were going. Returns true if they moved.
+fn step(map: &mut Map, player_pos: Point, pos: &mut Position, viewshed: &mut Viewshed, dest: usize) -> bool {
+    if map.blocked[dest] || dest == map.xy_idx(player_pos.x, player_pos.y) { return false; }
+    let (x, y) = (dest as i32 % map.width, dest as i32 / map.width);
+    let (x, y) = map.slide(x, y, x - pos.x, y - pos.y);
+    let idx = map.xy_idx(pos.x, pos.y);
+    map.blocked[idx] = false;
+    pos.x = x;
+    pos.y = y;
+    let idx = map.xy_idx(x, y);
+    map.blocked[idx] = true;
+    viewshed.dirty = true;
+    true
+}
+
+/// The last stage of the AI: carries out the moves the other stages asked for. Monsters can work
+/// door handles, so spend their turn opening any door in their way; panicking animals will run
+/// through anything to get away. Everyone's turn is over once this has run.
+pub struct MovementSystem {}
+
+impl<'a> System<'a> for MovementSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        Entities<'a>,
+        WriteStorage<'a, MyTurn>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, WantsToApproach>,
+        WriteStorage<'a, WantsToFlee>,
+        WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, StatusEffects>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Herbivore>,
+        ReadStorage<'a, Carnivore>,
+        WriteStorage<'a, Door>,
+        WriteStorage<'a, BlocksTile>,
+        WriteStorage<'a, BlocksVisibility>,
+        WriteStorage<'a, Renderable>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut map, player_pos, entities, mut turns, mut positions, mut viewsheds, mut wants_approach,
+            mut wants_flee, mut entity_moved, mut status_effects, monsters, herbivores, carnivores,
+            mut doors, mut blocks_movement, mut blocks_visibility, mut renderables, names, mut gamelog,
+        ) = data;
+
+        let mut opened_door = false;
+
+        for (entity, pos, viewshed, approach) in (&entities, &mut positions, &mut viewsheds, &wants_approach).join() {
+            if struggle(&mut status_effects, entity) { continue; }
+            let my_idx = map.xy_idx(pos.x, pos.y);
+
+            // Whoever they're heading for blocks their own tile, which the path has to end on
+            let was_blocked = map.blocked[approach.idx];
+            map.blocked[approach.idx] = false;
+            map.path_through_doors = monsters.get(entity).is_some();
+            let path = rltk::a_star_search(my_idx, approach.idx, &*map);
+            map.path_through_doors = false;
+            map.blocked[approach.idx] = was_blocked;
+            if !path.success || path.steps.len() < 2 { continue; }
+
+            let next_idx = path.steps[1];
+            if map.closed_doors.contains(&next_idx) {
+                // Spend the turn opening the door, rather than walking through it
+                let door = map.tile_content[next_idx].iter().find(|e| doors.get(**e).is_some()).copied();
+                if let Some(door) = door {
+                    set_door_open(door, true, &mut doors, &mut blocks_visibility, &mut blocks_movement, &mut renderables);
+                    map.closed_doors.remove(&next_idx);
+                    map.blocked[next_idx] = false;
+                    opened_door = true;
+                    if map.visible_tiles[next_idx] {
+                        if let Some(name) = names.get(entity) {
+                            gamelog.entries.push(format!("{} opens a door.", name.name));
+                        }
+                    }
+                }
+                continue;
+            }
+            if !step(&mut map, *player_pos, pos, viewshed, next_idx) { continue; }
+            entity_moved.insert(entity, EntityMoved{}).expect("Unable to insert marker");
+
+            // Hasted creatures cover twice the ground, short of walking into where they're going
+            let hasted = status_effects.get(entity).is_some_and(|s| s.has(StatusEffect::Haste));
+            if hasted && path.steps.len() > 3 && map.xy_idx(pos.x, pos.y) == next_idx {
+                let second_idx = path.steps[2];
+                if !map.closed_doors.contains(&second_idx) {
+                    step(&mut map, *player_pos, pos, viewshed, second_idx);
+                }
+            }
+        }
+
+        for (entity, pos, viewshed, flee) in (&entities, &mut positions, &mut viewsheds, &wants_flee).join() {
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            map.path_through_hazards = herbivores.get(entity).is_some() || carnivores.get(entity).is_some();
+            let dest = flee_destination(&mut map, my_idx, &flee.indices);
+            map.path_through_hazards = false;
+            if let Some(dest) = dest {
+                if !struggle(&mut status_effects, entity) && step(&mut map, *player_pos, pos, viewshed, dest) {
+                    entity_moved.insert(entity, EntityMoved{}).expect("Unable to insert marker");
+                }
+            }
+        }
+
+        wants_approach.clear();
+        wants_flee.clear();
+        turns.clear();
+
+        // Anyone might be able to see through a door which has just been opened
+        if opened_door {
+            for viewshed in (&mut viewsheds).join() {
+                viewshed.dirty = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use specs::prelude::*;
+use crate::{MyTurn, Quips, Name, Viewshed, WantsToReact, Reaction, gamelog::GameLog};
+use rltk::{Point};
+
+/// Anyone with something to say might say it now and then, when the player's in sight, unless
+/// they're busy running away.
+pub struct QuipSystem {}
+
+impl<'a> System<'a> for QuipSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Quips>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, MyTurn>,
+        ReadExpect<'a, Point>,
+        ReadStorage<'a, Viewshed>,
+        ReadStorage<'a, WantsToReact>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut gamelog, mut quips, names, turns, player_pos, viewsheds, wants_react, mut rng) = data;
+
+        for (entity, quip, name, viewshed, _turn) in (&entities, &mut quips, &names, &viewsheds, &turns).join() {
+            if wants_react.get(entity).is_some_and(|d| d.reaction == Reaction::Flee) { continue; }
+            if !quip.available.is_empty() && viewshed.visible_tiles.contains(&player_pos) && rng.roll_dice(1, 6) == 1 {
+                let quip_index = if quip.available.len() == 1 { 0 } else { (rng.roll_dice(1, quip.available.len() as i32)-1) as usize };
+                gamelog.entries.push(
+                    format!("{} says \"{}\"", name.name, quip.available[quip_index])
+                );
+                quip.available.remove(quip_index);
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use specs::prelude::*;
+use crate::{
+    MyTurn, Map, Position, WantsToReact, Reaction, WantsToMelee, WantsToShoot, WantsToApproach,
+    WantsToFlee, SpecialAbilities, WantsToUseAbility, Pools, Equipped, EquipmentSlot, RangedWeapon,
+    RemembersPlayer, Chasing, Herbivore, Name, gamelog::GameLog,
+};
+use rltk::{Point};
+
+/// Picks one of a monster's special abilities to use this turn, if one is ready and suits how far
+/// away its target is (and how hurt it is), and starts its cooldown. Returns the spell to cast,
+/// and whether the monster casts it on itself.
+fn choose_ability(abilities: &mut SpecialAbilities, distance: f32, hurt: bool, rng: &mut rltk::RandomNumberGenerator) -> Option<(String, bool)> {
+    for ability in abilities.abilities.iter_mut() {
+        if ability.ready_in > 0 || (ability.when_hurt && !hurt) { continue; }
+        if distance < ability.min_range || distance > ability.range { continue; }
+        if rng.roll_dice(1, 100) as f32 > ability.chance * 100.0 { continue; }
+        ability.ready_in = ability.cooldown;
+        return Some((ability.spell.clone(), ability.self_cast));
+    }
+    None
+}
+
+/// Acts on what each creature has decided about the most pressing thing it can see. Attackers use
+/// a special ability if the moment's right for one, hit whatever's next to them, shoot if they're
+/// holding a bow and otherwise close in, remembering where they last saw their quarry. The
+/// fearful run, and followers tag along without getting underfoot.
+pub struct ReactionAI {}
+
+impl<'a> System<'a> for ReactionAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, WantsToReact>,
+        WriteStorage<'a, WantsToMelee>,
+        WriteStorage<'a, WantsToShoot>,
+        WriteStorage<'a, WantsToApproach>,
+        WriteStorage<'a, WantsToFlee>,
+        WriteStorage<'a, SpecialAbilities>,
+        WriteStorage<'a, WantsToUseAbility>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, RangedWeapon>,
+        WriteStorage<'a, RemembersPlayer>,
+        WriteStorage<'a, Chasing>,
+        ReadStorage<'a, Herbivore>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            map, entities, mut turns, positions, wants_react, mut wants_to_melee, mut wants_to_shoot,
+            mut wants_approach, mut wants_flee, mut special_abilities, mut wants_ability, pools,
+            equipped, ranged_weapons, mut remembers_player, mut chasing, herbivores, names,
+            mut gamelog, mut rng,
+        ) = data;
+
+        let mut turn_done: Vec<Entity> = Vec::new();
+        for (entity, _turn, pos, decision) in (&entities, &turns, &positions, &wants_react).join() {
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            let target_idx = map.xy_idx(decision.at.x, decision.at.y);
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), decision.at);
+
+            match decision.reaction {
+                Reaction::Ignore => continue,
+                Reaction::Flee => {
+                    // Grazing animals might not notice the danger
+                    if herbivores.get(entity).is_some() && rng.roll_dice(1, 6) == 1 { continue; }
+                    wants_flee.insert(entity, WantsToFlee{ indices: vec![target_idx] }).expect("Unable to insert intent");
+                }
+                Reaction::Follow => {
+                    if distance > 2.0 {
+                        wants_approach.insert(entity, WantsToApproach{ idx: target_idx }).expect("Unable to insert intent");
+                    }
+                }
+                Reaction::Attack => {
+                    // Keep track of the quarry, to go after them if they get out of sight
+                    if let Some(remembers) = remembers_player.get_mut(entity) {
+                        remembers.memory = i32::max(remembers.max_memory, remembers.memory);
+                        chasing.insert(entity, Chasing{ target: decision.target, last_seen: decision.at }).expect("Unable to insert chase");
+                    }
+
+                    let hurt = pools.get(entity).is_some_and(|p| p.hit_points.current * 2 <= p.hit_points.max);
+                    let ability = special_abilities.get_mut(entity).and_then(|special| choose_ability(special, distance, hurt, &mut rng));
+                    // Archers would rather shoot from where they are than close in
+                    let range = (&equipped, &ranged_weapons).join()
+                        .find(|(wielded, _ranged)| wielded.owner == entity && wielded.slot == EquipmentSlot::Melee)
+                        .map(|(_wielded, ranged)| ranged.range);
+
+                    if let Some((spell, self_cast)) = ability {
+                        let target = if self_cast { None } else { Some(decision.at) };
+                        wants_ability.insert(entity, WantsToUseAbility{ spell: spell.clone(), target }).expect("Unable to insert intent");
+                        if map.visible_tiles[my_idx] {
+                            if let Some(name) = names.get(entity) {
+                                gamelog.entries.push(format!("{} uses {}!", name.name, spell));
+                            }
+                        }
+                    } else if distance < 1.5 {
+                        wants_to_melee.insert(entity, WantsToMelee{ target: decision.target }).expect("Unable to insert attack.");
+                    } else if range.is_some_and(|range| distance <= range as f32) {
+                        wants_to_shoot.insert(entity, WantsToShoot{ target: decision.target }).expect("Unable to insert attack.");
+                    } else {
+                        wants_approach.insert(entity, WantsToApproach{ idx: target_idx }).expect("Unable to insert intent");
+                    }
+                }
+            }
+            turn_done.push(entity);
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use specs::prelude::*;
+use crate::{
+    MyTurn, Map, Position, StatusEffects, StatusEffect, WantsToApproach, WantsToFlee,
+    particle_system::ParticleBuilder, status_effect_system::{impairment, Impairment, stagger_destination},
+};
+use rltk::{Point};
+
+/// Takes turns away from anyone whose status effects stop them acting as they'd like. The
+/// stunned and slowed lose the turn, the confused blunder about and the frightened run from the
+/// player.
+pub struct TurnStatusSystem {}
+
+impl<'a> System<'a> for TurnStatusSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        Entities<'a>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, StatusEffects>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        WriteStorage<'a, WantsToApproach>,
+        WriteStorage<'a, WantsToFlee>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            map, player_pos, entities, mut turns, positions, status_effects, mut particle_builder,
+            mut rng, mut wants_approach, mut wants_flee,
+        ) = data;
+
+        let mut turn_done: Vec<Entity> = Vec::new();
+        for (entity, _turn, pos) in (&entities, &turns, &positions).join() {
+            let status = status_effects.get(entity);
+            let impaired = impairment(status, &mut rng);
+            if impaired == Impairment::Staggers || status.is_some_and(|s| s.has(StatusEffect::Stun)) {
+                particle_builder.request(
+                    pos.x,
+                    pos.y,
+                    rltk::RGB::named(rltk::MAGENTA),
+                    rltk::RGB::named(rltk::BLACK),
+                    rltk::to_cp437('?'),
+                    200.0
+                );
+            }
+            match impaired {
+                Impairment::None => continue,
+                Impairment::LosesTurn => {}
+                Impairment::Staggers => {
+                    if let Some(dest) = stagger_destination(&map, map.xy_idx(pos.x, pos.y), &mut rng) {
+                        wants_approach.insert(entity, WantsToApproach{ idx: dest }).expect("Unable to insert intent");
+                    }
+                }
+                Impairment::Flees => {
+                    let threat = map.xy_idx(player_pos.x, player_pos.y);
+                    wants_flee.insert(entity, WantsToFlee{ indices: vec![threat] }).expect("Unable to insert intent");
+                }
+            }
+            turn_done.push(entity);
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use specs::prelude::*;
+use crate::{MyTurn, Map, Position, Bystander, WantsToApproach};
+use rltk::{Point};
+
+/// Bystanders with nothing better to do wander about at random, keeping out of harm's way.
+pub struct WanderAI {}
+
+impl<'a> System<'a> for WanderAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        Entities<'a>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Bystander>,
+        WriteStorage<'a, WantsToApproach>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (map, player_pos, entities, mut turns, positions, bystanders, mut wants_approach, mut rng) = data;
+
+        let mut turn_done: Vec<Entity> = Vec::new();
+        for (entity, _turn, pos, _bystander) in (&entities, &turns, &positions, &bystanders).join() {
+            turn_done.push(entity);
+            let (mut x, mut y) = (pos.x, pos.y);
+            match rng.roll_dice(1, 5) {
+                1 => x -= 1,
+                2 => x += 1,
+                3 => y -= 1,
+                4 => y += 1,
+                _ => continue,
+            }
+            if x < 1 || x > map.width-2 || y < 1 || y > map.height-2 { continue; }
+            if x == player_pos.x && y == player_pos.y { continue; }
+            let dest_idx = map.xy_idx(x, y);
+            if !map.blocked[dest_idx] && !map.is_hazardous(dest_idx) {
+                wants_approach.insert(entity, WantsToApproach{ idx: dest_idx }).expect("Unable to insert intent");
+            }
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+    }
+}
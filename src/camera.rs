@@ -1,7 +1,7 @@
 use specs::prelude::*;
 use super::{Map, Position, Renderable, Hidden};
 use rltk::{Point, Rltk, RGB};
-use crate::map::tile_glyph;
+use crate::map::{tile_glyph, cloud_tint};
 
 const SHOW_BOUNDARIES: bool = true;
 const CONSTRAIN_CAMERA: bool = true;
@@ -21,24 +21,36 @@ pub fn get_screen_bounds(ecs: &World, _ctx: &mut Rltk) -> (i32, i32, i32, i32) {
     if CONSTRAIN_CAMERA {
         // Don't let the camera stray outside the bounds of the map
         let map = ecs.fetch::<Map>();
-        if max_x > map.width {
-            let correction = max_x - map.width;
-            min_x -= correction;
-            max_x -= correction;
-        }
-        if max_y > map.height {
-            let correction = max_y - map.height;
-            min_y -= correction;
-            max_y -= correction;
-        }
-        if min_x < 0 {
-            max_x += i32::abs(min_x);
-            min_x = 0;
+        if map.width < x_chars {
+            // Maps narrower than the screen sit in the middle of it
+            min_x = (map.width - x_chars) / 2;
+            max_x = min_x + x_chars;
+        } else {
+            if max_x > map.width {
+                let correction = max_x - map.width;
+                min_x -= correction;
+                max_x -= correction;
+            }
+            if min_x < 0 {
+                max_x += i32::abs(min_x);
+                min_x = 0;
+            }
         }
-        if min_y < 0 {
-            let correction = i32::abs(min_y);
-            max_y += correction;
-            min_y = 0;
+        if map.height < y_chars {
+            // As do maps shorter than the screen
+            min_y = (map.height - y_chars) / 2;
+            max_y = min_y + y_chars;
+        } else {
+            if max_y > map.height {
+                let correction = max_y - map.height;
+                min_y -= correction;
+                max_y -= correction;
+            }
+            if min_y < 0 {
+                let correction = i32::abs(min_y);
+                max_y += correction;
+                min_y = 0;
+            }
         }
     }
 
@@ -83,8 +95,8 @@ pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
         if map.visible_tiles[idx] {
             let entity_screen_x = pos.x - min_x;
             let entity_screen_y = pos.y - min_y;
-            if entity_screen_x >= 0 && entity_screen_x <= map_width && entity_screen_y >= 0 && entity_screen_y <= map_height {
-                ctx.set(entity_screen_x, entity_screen_y, render.fg, render.bg, render.glyph);
+            if entity_screen_x >= 0 && entity_screen_x < max_x - min_x && entity_screen_y >= 0 && entity_screen_y < max_y - min_y {
+                ctx.set(entity_screen_x, entity_screen_y, render.fg, cloud_tint(&map, idx, render.bg, 1.0), render.glyph);
             }
         }
     }
@@ -0,0 +1,120 @@
+use specs::prelude::*;
+use super::{
+    Map, Pools, Name, RunState, SufferDamage, Viewshed, StatusEffects, StatusEffect, gamelog::GameLog, Cloud, cloud_info,
+    map::{tile_walkable, tile_opaque}, parse_dice_string,
+};
+use std::collections::HashMap;
+
+/// Releases a cloud over a tile, at the thickness the raws give it. A thicker cloud already
+/// hanging there isn't pushed aside.
+pub fn add_cloud(map: &mut Map, idx: usize, name: &str) {
+    let density = match cloud_info(name) {
+        None => {
+            rltk::console::log(format!("WARNING - no cloud called {}", name));
+            return;
+        }
+        Some(info) => info.density,
+    };
+    if tile_opaque(map.tiles[idx]) { return; }
+    if map.clouds.get(&idx).is_some_and(|cloud| cloud.density >= density) { return; }
+    map.clouds.insert(idx, Cloud{ name: name.to_string(), density });
+}
+
+/// Lingering clouds of gas, mist and smoke. Each turn they affect anyone standing in them, drift
+/// into open neighbouring tiles (getting thinner as they go) and slowly dissipate.
+pub struct CloudSystem {}
+
+impl<'a> System<'a> for CloudSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteStorage<'a, Pools>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, StatusEffects>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut map, player_entity, runstate, mut pools, names, mut inflict_damage,
+            mut viewsheds, mut status_effects, mut gamelog, mut rng,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn { return; }
+        if map.clouds.is_empty() { return; }
+
+        let mut clouds: Vec<(usize, Cloud)> = map.clouds.iter().map(|(idx, cloud)| (*idx, cloud.clone())).collect();
+        clouds.sort_unstable_by_key(|(idx, _cloud)| *idx); // Keep the dice rolls repeatable
+
+        // Affect whoever is standing in a cloud
+        for (idx, cloud) in clouds.iter() {
+            let info = match cloud_info(&cloud.name) {
+                None => continue,
+                Some(info) => info,
+            };
+            for victim in map.tile_content[*idx].iter() {
+                let stats = match pools.get_mut(*victim) {
+                    None => continue,
+                    Some(stats) => stats,
+                };
+                let is_player = *victim == *player_entity;
+                if let Some(damage) = &info.damage {
+                    let (n_dice, die_type, bonus) = parse_dice_string(damage);
+                    SufferDamage::new_damage(&mut inflict_damage, *victim, rng.roll_dice(n_dice, die_type) + bonus, info.damage_type, false);
+                    if is_player {
+                        gamelog.entries.push(format!("You choke on the {}!", cloud.name));
+                    } else if let (true, Some(name)) = (map.visible_tiles[*idx], names.get(*victim)) {
+                        gamelog.entries.push(format!("{} chokes on the {}!", name.name, cloud.name));
+                    }
+                }
+                if info.confusion > 0 {
+                    StatusEffects::apply(&mut status_effects, *victim, StatusEffect::Confusion, info.confusion);
+                    if is_player {
+                        gamelog.entries.push(format!("The {} makes your head spin!", cloud.name));
+                    }
+                }
+                if info.healing > 0 && stats.hit_points.current < stats.hit_points.max {
+                    stats.hit_points.current = i32::min(stats.hit_points.max, stats.hit_points.current + info.healing);
+                    if is_player {
+                        gamelog.entries.push(format!("The {} soothes your wounds.", cloud.name));
+                    }
+                }
+            }
+        }
+
+        // Drift into open neighbouring tiles, then thin out
+        let mut next: HashMap<usize, Cloud> = map.clouds.clone();
+        for (idx, cloud) in clouds.iter() {
+            let info = match cloud_info(&cloud.name) {
+                None => continue,
+                Some(info) => info,
+            };
+            if !info.spreads || cloud.density < 2 { continue; }
+            let (x, y) = (*idx as i32 % map.width, *idx as i32 / map.width);
+            for (nx, ny) in [(x-1, y), (x+1, y), (x, y-1), (x, y+1)].iter() {
+                if *nx < 0 || *nx > map.width-1 || *ny < 0 || *ny > map.height-1 { continue; }
+                let neighbour = map.xy_idx(*nx, *ny);
+                if !tile_walkable(map.tiles[neighbour]) || tile_opaque(map.tiles[neighbour]) { continue; }
+                let density = cloud.density - 1;
+                if next.get(&neighbour).is_none_or(|existing| existing.density < density) {
+                    next.insert(neighbour, Cloud{ name: cloud.name.clone(), density });
+                }
+            }
+        }
+        for cloud in next.values_mut() {
+            cloud.density -= cloud_info(&cloud.name).map_or(1, |info| info.decay);
+        }
+        next.retain(|_idx, cloud| cloud.density > 0);
+        map.clouds = next;
+
+        // Clouds which block the view have moved, so everyone needs to take another look
+        for viewshed in (&mut viewsheds).join() {
+            viewshed.dirty = true;
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use specs::prelude::*;
 use specs_derive::*;
 use rltk::{RGB};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use serde::{Serialize, Deserialize};
 use specs::saveload::{Marker, ConvertSaveload};
 use specs::error::NoError;
@@ -21,6 +21,13 @@ pub struct Renderable {
     pub render_order: i32,
 }
 
+/// Something which lights up the tiles around it.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct LightSource {
+    pub color: RGB,
+    pub range: i32,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct ParticleLifetime {
     pub lifetime_ms: f32,
@@ -55,11 +62,13 @@ pub struct Attributes {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
-pub enum Skill { Melee, Defense, Magic }
+pub enum Skill { Melee, Defense, Magic, Traps, Ranged, Stealth, Perception }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Skills {
-    pub skills: HashMap<Skill, i32>
+    pub skills: HashMap<Skill, i32>,
+    /// How many successful uses of each skill have counted towards its next rank
+    pub practice: HashMap<Skill, i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -89,6 +98,12 @@ pub struct HungerClock {
     pub duration: i32,
 }
 
+/// Someone treading water, and how many turns they've been at it.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Swimming {
+    pub turns: i32,
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct BlocksTile {}
 
@@ -121,6 +136,77 @@ pub struct Carnivore {}
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Herbivore {}
 
+/// Which side someone is on. How the factions feel about each other comes from the raws.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Faction {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum Reaction { Ignore, Attack, Flee, Follow }
+
+/// What a creature has decided to do this turn about the most pressing thing it can see, which is
+/// `target` (last seen at `at`).
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToReact {
+    pub reaction: Reaction,
+    pub target: Entity,
+    pub at: rltk::Point,
+}
+
+/// Given to everything with an AI at the start of the monster turn, and taken away again by
+/// whichever stage of the AI decides what it does with the turn.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MyTurn {}
+
+/// Asks the movement system to take a step along the way to a tile.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToApproach {
+    pub idx: usize,
+}
+
+/// Asks the movement system to take a step away from some tiles.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToFlee {
+    pub indices: Vec<usize>,
+}
+
+/// Who a creature is after, and where it last saw them.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Chasing {
+    pub target: Entity,
+    pub last_seen: rltk::Point,
+}
+
+/// The factions the player has turned against themselves.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Grudges {
+    pub factions: HashSet<String>,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum Alertness { Asleep, Unaware, Alert }
+
+/// Whether a monster knows the player is about. Only alert monsters come after them.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Awareness {
+    pub state: Alertness,
+}
+
+/// What a monster does once its nerve breaks.
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum MoraleBreak { Flee, Surrender, CallForHelp }
+
+/// How much fight a monster has left in it. It drops as they're hurt and as their allies fall
+/// around them, and once it's gone they break. Mindless things like the undead don't have any.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Morale {
+    pub current: i32,
+    pub max: i32,
+    pub on_break: MoraleBreak,
+    pub broken: bool,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct RemembersPlayer {
     // How long the enemy will continue to pursue the player after losing sight.
@@ -128,17 +214,31 @@ pub struct RemembersPlayer {
     pub memory: i32,
 }
 
+/// The kinds of harm something can do. `Untyped` harm, like starving, can't be resisted.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum DamageType { Slashing, Piercing, Bludgeoning, Fire, Cold, Poison, Acid, Untyped }
+
+/// Kinds of damage which something takes half of, none of, or double. Creatures can have these,
+/// and so can armour, which protects whoever is wearing it.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Resistances {
+    pub resist: Vec<DamageType>,
+    pub immune: Vec<DamageType>,
+    pub weak: Vec<DamageType>,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>
+    /// Each hit taken this turn, with its damage type and whether the player dealt it
+    pub amount: Vec<(i32, DamageType, bool)>
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32, damage_type: DamageType, from_player: bool) {
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push((amount, damage_type, from_player));
         } else {
-            let dmg = SufferDamage { amount: vec![amount] };
+            let dmg = SufferDamage { amount: vec![(amount, damage_type, from_player)] };
             store.insert(victim, dmg).expect("Unable to insert damage!");
         }
     }
@@ -150,7 +250,40 @@ pub struct EntityMoved {}
 // Items
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct Item {}
+pub struct Item {
+    /// In pounds; for ammunition this is the weight of a single round
+    pub weight: f32,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum EncumbranceState { Unburdened, Burdened, Overloaded }
+
+impl EncumbranceState {
+    /// How much carrying this much takes off your quickness.
+    pub fn quickness_penalty(&self) -> i32 {
+        match self {
+            EncumbranceState::Unburdened => 0,
+            EncumbranceState::Burdened => 1,
+            EncumbranceState::Overloaded => 3,
+        }
+    }
+
+    /// How much carrying this much takes off your attack rolls.
+    pub fn hit_penalty(&self) -> i32 {
+        match self {
+            EncumbranceState::Overloaded => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// How much someone is carrying, against how much their might lets them carry.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Encumbrance {
+    pub weight: f32,
+    pub capacity: f32,
+    pub state: EncumbranceState,
+}
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct ProvidesHealing {
@@ -176,6 +309,7 @@ pub struct Ranged {
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -183,14 +317,109 @@ pub struct AreaOfEffect {
     pub radius: i32,
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct Stunned {
+/// Conditions which linger on someone for a number of turns.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum StatusEffect { Stun, Confusion, Poison, Regeneration, Haste, Slow, Blindness, Fear, Entangled }
+
+impl StatusEffect {
+    /// How the effect is described in the status area and the log.
+    pub fn describe(&self) -> &str {
+        match self {
+            StatusEffect::Stun => "Stunned",
+            StatusEffect::Confusion => "Confused",
+            StatusEffect::Poison => "Poisoned",
+            StatusEffect::Regeneration => "Regenerating",
+            StatusEffect::Haste => "Hasted",
+            StatusEffect::Slow => "Slowed",
+            StatusEffect::Blindness => "Blind",
+            StatusEffect::Fear => "Afraid",
+            StatusEffect::Entangled => "Entangled",
+        }
+    }
+}
+
+/// A status effect someone is under, and how many more turns it lasts. `potency` is how hard it
+/// hits each turn, for effects like poison which build up with each dose.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActiveEffect {
+    pub effect: StatusEffect,
     pub turns: i32,
+    pub potency: i32,
+}
+
+/// Everything which is currently affecting someone.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct StatusEffects {
+    pub effects: Vec<ActiveEffect>,
+}
+
+impl StatusEffects {
+    /// Puts an effect on the target. Another dose of poison makes it stronger, regeneration lasts
+    /// longer, and anything else starts over if the new dose lasts longer. Haste and slowness
+    /// cancel each other out.
+    pub fn apply(store: &mut WriteStorage<StatusEffects>, target: Entity, effect: StatusEffect, turns: i32) {
+        if store.get(target).is_none() {
+            store.insert(target, StatusEffects{ effects: Vec::new() }).expect("Unable to insert status effects");
+        }
+        let status = store.get_mut(target).unwrap();
+        let opposite = match effect {
+            StatusEffect::Haste => Some(StatusEffect::Slow),
+            StatusEffect::Slow => Some(StatusEffect::Haste),
+            _ => None,
+        };
+        if let Some(opposite) = opposite {
+            if status.has(opposite) {
+                status.effects.retain(|e| e.effect != opposite);
+                return;
+            }
+        }
+        match status.effects.iter_mut().find(|e| e.effect == effect) {
+            None => status.effects.push(ActiveEffect{ effect, turns, potency: 1 }),
+            Some(existing) => match effect {
+                StatusEffect::Poison => {
+                    existing.potency += 1;
+                    existing.turns = i32::max(existing.turns, turns);
+                }
+                StatusEffect::Regeneration => existing.turns += turns,
+                _ => existing.turns = i32::max(existing.turns, turns),
+            }
+        }
+    }
+
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.effects.iter().any(|e| e.effect == effect)
+    }
+
+    pub fn turns(&self, effect: StatusEffect) -> Option<i32> {
+        self.effects.iter().find(|e| e.effect == effect).map(|e| e.turns)
+    }
+}
+
+/// Status effects (and how many turns they last) put on whoever this item, trap or attack hits.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsStatus {
+    pub effects: Vec<(StatusEffect, i32)>,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct MagicMapper {}
 
+/// An item which sets fire to wherever it lands.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct StartsFire {}
+
+/// An item which releases a cloud of gas (or mist) wherever it lands.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct CreatesCloud {
+    pub cloud: String,
+}
+
+/// Someone who has caught fire, and how many more turns they'll burn for.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Burning {
+    pub turns: i32,
+}
+
 // Equipment
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum EquipmentSlot { Melee, Shield, Head, Torso, Legs, Feet, Hands }
@@ -198,6 +427,7 @@ pub enum EquipmentSlot { Melee, Shield, Head, Torso, Legs, Feet, Hands }
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Equippable {
     pub slot: EquipmentSlot,
+    pub hands: i32,
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -217,21 +447,131 @@ pub enum WeaponAttribute { Might, Quickness }
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct MeleeWeapon {
     pub attribute: WeaponAttribute,
+    pub damage_type: DamageType,
     pub damage_n_dice: i32,
     pub damage_die_type: i32,
     pub damage_bonus: i32,
     pub hit_bonus: i32,
 }
 
+/// A weapon which attacks from a distance. It either fires `ammo` carried in the wielder's pack,
+/// or (if it's `thrown`) flies off itself and has to be picked up again.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub attribute: WeaponAttribute,
+    pub damage_type: DamageType,
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+    pub hit_bonus: i32,
+    pub ammo: Option<String>,
+    pub thrown: bool,
+}
+
+/// A bundle of arrows, bolts, stones or the like, and how many are left.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Ammunition {
+    pub kind: String,
+    pub count: i32,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToShoot {
+    pub target: Entity,
+}
+
+/// A spell, made from its raws template whenever it's cast and gone once it has been. It carries
+/// the same effect components as a consumable item.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Spell {
+    pub mana_cost: i32,
+    pub difficulty: i32,
+}
+
+/// The names of the spells someone knows how to cast.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct KnownSpells {
+    pub spells: Vec<String>,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToCastSpell {
+    pub spell: Entity,
+    pub target: Option<rltk::Point>,
+}
+
+/// Something a monster can do besides hitting you, by casting a spell from the raws. They'll only
+/// try it when their target is between `min_range` and `range` away, and then only `chance` of
+/// the time. Once used, it takes `cooldown` turns to be ready again.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SpecialAbility {
+    pub spell: String,
+    pub chance: f32,
+    pub range: f32,
+    pub min_range: f32,
+    pub cooldown: i32,
+    pub ready_in: i32,
+    /// Cast on themselves rather than at their target
+    pub self_cast: bool,
+    /// Only used when they're down to half their health or less
+    pub when_hurt: bool,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct SpecialAbilities {
+    pub abilities: Vec<SpecialAbility>,
+}
+
+/// A monster has picked one of its abilities to use this turn. The spell is made from the raws
+/// before it's cast.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToUseAbility {
+    pub spell: String,
+    pub target: Option<rltk::Point>,
+}
+
+/// A spell which rushes the caster up to their target.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Charges {}
+
+/// A spell which calls allies to the caster's side.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Summons {
+    pub mob: String,
+    pub count: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToSummon {
+    pub mob: String,
+    pub count: i32,
+}
+
+/// A spellbook, which teaches the spell it's named after when read.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct TeachesSpell {
+    pub spell: String,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Wearable {
     pub armour_class: f32,
     pub slot: EquipmentSlot,
 }
 
+/// A shield which can actively block blows, soaking up `block_amount` damage when it does.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Shield {
+    pub block_chance: i32,
+    pub block_amount: i32,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct NaturalAttack {
     pub name: String,
+    pub damage_type: DamageType,
+    pub inflicts: Vec<(StatusEffect, i32)>,
     pub damage_n_dice: i32,
     pub damage_die_type: i32,
     pub damage_bonus: i32,
@@ -281,6 +621,9 @@ pub struct WantsToRemoveItem {
     pub item: Entity,
 }
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToSearch {}
+
 // Terrain
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -296,3 +639,50 @@ pub struct SingleActivation {}
 pub struct Door {
     pub open: bool
 }
+
+/// A trap which sends its victim somewhere else on the level.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Teleports {}
+
+/// A trap which alerts every monster within `radius` tiles to the player.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RaisesAlarm {
+    pub radius: i32,
+}
+
+/// A trap which drops its victim down to the next level.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Pitfall {}
+
+/// A trap which holds its victim in place for a number of turns.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Entangles {
+    pub turns: i32,
+}
+
+/// A trap which can be disarmed, and how hard it is to do so.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Disarmable {
+    pub difficulty: i32,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToDisarm {
+    pub trap: Entity,
+}
+
+/// A door which looks like a wall until it has been found.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct SecretDoor {}
+
+/// A door which can only be opened by someone carrying a key with a matching `lock`.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Locked {
+    pub lock: String,
+}
+
+/// An item which opens any `Locked` door with the same `lock`.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Key {
+    pub lock: String,
+}
@@ -1,30 +1,127 @@
 use specs::prelude::*;
 use super::{
     Pools, SufferDamage, Player, gamelog::GameLog, Name, RunState,
-    Position, Map, Equipped, InBackpack, LootTable
+    Position, Map, Equipped, InBackpack, LootTable, Resistances, DamageType, xp_for_kill,
+    Morale, Monster, Viewshed, Awareness, Alertness, Faction, Grudges,
+    raws::{RAWS, faction_holds_grudges},
 };
 
+/// How someone fares against a kind of damage, taking into account their own resistances and
+/// those of whatever they're wearing. Being resistant and weak at once cancels out.
+#[derive(PartialEq, Copy, Clone)]
+enum DamageResponse { Normal, Resisted, Immune, Weak }
+
+fn damage_response(
+    victim: Entity,
+    damage_type: DamageType,
+    resistances: &ReadStorage<Resistances>,
+    equipped: &ReadStorage<Equipped>,
+) -> DamageResponse {
+    if damage_type == DamageType::Untyped { return DamageResponse::Normal; }
+    let mut protection: Vec<&Resistances> = (equipped, resistances).join()
+        .filter(|(worn, _res)| worn.owner == victim)
+        .map(|(_worn, res)| res)
+        .collect();
+    if let Some(own) = resistances.get(victim) {
+        protection.push(own);
+    }
+    if protection.iter().any(|r| r.immune.contains(&damage_type)) {
+        return DamageResponse::Immune;
+    }
+    let resisted = protection.iter().any(|r| r.resist.contains(&damage_type));
+    let weak = protection.iter().any(|r| r.weak.contains(&damage_type));
+    match (resisted, weak) {
+        (true, false) => DamageResponse::Resisted,
+        (false, true) => DamageResponse::Weak,
+        _ => DamageResponse::Normal,
+    }
+}
+
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'a, Pools>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Entity>,
+        WriteStorage<'a, Morale>,
+        WriteStorage<'a, Awareness>,
+        ReadStorage<'a, Faction>,
+        WriteStorage<'a, Grudges>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
+        let (
+            mut stats, mut damage, positions, mut map, entities, resistances, equipped, names, mut log,
+            player_entity, mut morale, mut awareness, factions, mut grudges,
+        ) = data;
 
+        let mut xp_gain = 0;
         for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            stats.hit_points.current -= damage.amount.iter().sum::<i32>();
-            let pos = positions.get(entity);
-            if let Some(pos) = pos {
-                let idx = map.xy_idx(pos.x, pos.y);
+            let idx = positions.get(entity).map(|pos| map.xy_idx(pos.x, pos.y));
+            let seen = idx.is_some_and(|idx| map.visible_tiles[idx]);
+            let mut total = 0;
+            for (amount, damage_type, _from_player) in damage.amount.iter() {
+                let response = damage_response(entity, *damage_type, &resistances, &equipped);
+                total += match response {
+                    DamageResponse::Normal => *amount,
+                    DamageResponse::Resisted => amount / 2,
+                    DamageResponse::Immune => 0,
+                    DamageResponse::Weak => amount * 2,
+                };
+                if let (true, Some(name), true) = (seen, names.get(entity), *amount > 0) {
+                    let kind = format!("{:?}", damage_type).to_lowercase();
+                    match response {
+                        DamageResponse::Normal => {}
+                        DamageResponse::Resisted => log.entries.push(format!("{} resists the {} damage.", name.name, kind)),
+                        DamageResponse::Immune => log.entries.push(format!("{} is immune to {} damage!", name.name, kind)),
+                        DamageResponse::Weak => log.entries.push(format!("{} takes extra {} damage!", name.name, kind)),
+                    }
+                }
+            }
+            let was_alive = stats.hit_points.current > 0;
+            stats.hit_points.current -= total;
+            if let (Some(idx), true) = (idx, total > 0) {
                 map.bloodstains.insert(idx);
             }
+
+            // Nobody sleeps through being hurt
+            if let (Some(aware), true) = (awareness.get_mut(entity), total > 0) {
+                aware.state = Alertness::Alert;
+            }
+
+            // Getting hurt is bad for morale; losing a tenth of your health costs a point of it
+            if let (Some(nerve), true) = (morale.get_mut(entity), total > 0) {
+                nerve.current -= i32::max(1, total * 10 / i32::max(1, stats.hit_points.max));
+            }
+
+            // The player learns from whatever they kill
+            let killed_by_player = damage.amount.iter().any(|(_amount, _damage_type, from_player)| *from_player);
+
+            // Some factions don't forget being attacked
+            if let (true, Some(faction), Some(grudges)) = (killed_by_player, factions.get(entity), grudges.get_mut(*player_entity)) {
+                if faction_holds_grudges(&faction.name, &RAWS.lock().unwrap()) && grudges.factions.insert(faction.name.clone()) {
+                    log.entries.push(format!("You have angered the {}!", faction.name));
+                }
+            }
+            if was_alive && stats.hit_points.current < 1 && killed_by_player && entity != *player_entity {
+                xp_gain += xp_for_kill(stats.level);
+            }
+        }
+
+        if xp_gain > 0 {
+            if let Some(player_stats) = stats.get_mut(*player_entity) {
+                player_stats.xp += xp_gain;
+                log.entries.push(format!("You gain {} experience.", xp_gain));
+            }
         }
 
         damage.clear();
@@ -61,6 +158,25 @@ pub fn delete_the_dead(ecs: &mut World) {
         }
     }
 
+    // Seeing an ally fall shakes the other monsters
+    {
+        let monsters = ecs.read_storage::<Monster>();
+        let positions = ecs.read_storage::<Position>();
+        let viewsheds = ecs.read_storage::<Viewshed>();
+        let mut morale = ecs.write_storage::<Morale>();
+        for victim in dead.iter() {
+            if monsters.get(*victim).is_none() { continue; }
+            if let Some(pos) = positions.get(*victim) {
+                let fell_at = rltk::Point::new(pos.x, pos.y);
+                for (viewshed, nerve, _monster) in (&viewsheds, &mut morale, &monsters).join() {
+                    if viewshed.visible_tiles.contains(&fell_at) {
+                        nerve.current -= 3;
+                    }
+                }
+            }
+        }
+    }
+
     // Drop everything held by dead people
     let mut to_spawn: Vec<(String, Position)> = Vec::new();
     {
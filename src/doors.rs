@@ -0,0 +1,27 @@
+use specs::prelude::*;
+use super::{Door, BlocksTile, BlocksVisibility, Renderable};
+
+/// Opens or closes a door. Closed doors block both movement and line of sight, and open ones
+/// block neither.
+pub fn set_door_open(
+    door_entity: Entity,
+    open: bool,
+    doors: &mut WriteStorage<Door>,
+    blocks_visibility: &mut WriteStorage<BlocksVisibility>,
+    blocks_movement: &mut WriteStorage<BlocksTile>,
+    renderables: &mut WriteStorage<Renderable>,
+) {
+    if let Some(door) = doors.get_mut(door_entity) {
+        door.open = open;
+    }
+    if open {
+        blocks_visibility.remove(door_entity);
+        blocks_movement.remove(door_entity);
+    } else {
+        blocks_visibility.insert(door_entity, BlocksVisibility{}).expect("Unable to insert visibility blocker");
+        blocks_movement.insert(door_entity, BlocksTile{}).expect("Unable to insert tile blocker");
+    }
+    if let Some(render) = renderables.get_mut(door_entity) {
+        render.glyph = rltk::to_cp437(if open { '/' } else { '+' });
+    }
+}
@@ -0,0 +1,53 @@
+use specs::prelude::*;
+use super::{
+    Item, Equipped, InBackpack, Attributes, Encumbrance, EncumbranceState, gamelog::GameLog, Ammunition,
+    attr_bonus, carry_capacity, encumbrance_state,
+};
+
+/// Weighs up everything each carrier has on them, and slows them down if it's too much. Ammunition
+/// weighs as much as however many rounds are left.
+pub struct EncumbranceSystem {}
+
+impl<'a> System<'a> for EncumbranceSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, InBackpack>,
+        WriteStorage<'a, Attributes>,
+        WriteStorage<'a, Encumbrance>,
+        ReadStorage<'a, Ammunition>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, player_entity, mut gamelog, items, equipped, backpack, mut attributes, mut encumbrance, ammunition) = data;
+
+        for (entity, burden, attr) in (&entities, &mut encumbrance, &mut attributes).join() {
+            let mut weight = 0.0;
+            for (item, worn, ammo) in (&items, &equipped, ammunition.maybe()).join() {
+                if worn.owner == entity { weight += item.weight * ammo.map_or(1, |a| a.count) as f32; }
+            }
+            for (item, carried, ammo) in (&items, &backpack, ammunition.maybe()).join() {
+                if carried.owner == entity { weight += item.weight * ammo.map_or(1, |a| a.count) as f32; }
+            }
+
+            let capacity = carry_capacity(attr.might.base + attr.might.modifiers);
+            let state = encumbrance_state(weight, capacity);
+            if state != burden.state && entity == *player_entity {
+                gamelog.entries.push(match state {
+                    EncumbranceState::Unburdened => "You are no longer weighed down.".to_string(),
+                    EncumbranceState::Burdened => "You are burdened by everything you're carrying.".to_string(),
+                    EncumbranceState::Overloaded => "You are overloaded, and can barely move!".to_string(),
+                });
+            }
+            burden.weight = weight;
+            burden.capacity = capacity;
+            burden.state = state;
+
+            attr.quickness.modifiers = -state.quickness_penalty();
+            attr.quickness.bonus = attr_bonus(attr.quickness.base + attr.quickness.modifiers);
+        }
+    }
+}
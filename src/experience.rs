@@ -0,0 +1,70 @@
+use specs::prelude::*;
+use super::{
+    Pools, Attributes, Attribute, Skills, Skill, gamelog::GameLog, attr_bonus,
+    player_hp_at_level, mana_at_level, xp_to_next_level, xp_for_depth,
+};
+
+/// The attributes a levelling player can raise, in the order the level up screen lists them.
+pub const LEVEL_UP_ATTRIBUTES: [&str; 4] = ["Might", "Fitness", "Quickness", "Intelligence"];
+
+/// The skills a levelling player can train, in the order the level up screen lists them.
+pub const LEVEL_UP_SKILLS: [Skill; 7] = [
+    Skill::Melee, Skill::Defense, Skill::Magic, Skill::Traps, Skill::Ranged, Skill::Stealth, Skill::Perception,
+];
+
+/// True if the player has earned enough experience to go up a level.
+pub fn ready_to_level(ecs: &World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let pools = ecs.read_storage::<Pools>();
+    pools.get(*player_entity).is_some_and(|stats| stats.xp >= xp_to_next_level(stats.level))
+}
+
+/// Rewards the player for making it down to a new depth.
+pub fn reached_depth(ecs: &mut World, depth: i32) {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut pools = ecs.write_storage::<Pools>();
+    if let Some(stats) = pools.get_mut(*player_entity) {
+        stats.xp += xp_for_depth(depth);
+    }
+}
+
+/// Puts a point into one of the player's attributes, picked from `LEVEL_UP_ATTRIBUTES`.
+pub fn raise_attribute(ecs: &mut World, choice: usize) {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut attributes = ecs.write_storage::<Attributes>();
+    let attr = attributes.get_mut(*player_entity).unwrap();
+    let raised: &mut Attribute = match choice {
+        0 => &mut attr.might,
+        1 => &mut attr.fitness,
+        2 => &mut attr.quickness,
+        _ => &mut attr.intelligence,
+    };
+    raised.base += 1;
+    raised.bonus = attr_bonus(raised.base + raised.modifiers);
+    ecs.fetch_mut::<GameLog>().entries.push(format!("Your {} increases.", LEVEL_UP_ATTRIBUTES[choice].to_lowercase()));
+}
+
+/// Puts a rank into one of the player's skills.
+pub fn train_skill(ecs: &mut World, skill: Skill) {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut skills = ecs.write_storage::<Skills>();
+    let player_skills = skills.get_mut(*player_entity).unwrap();
+    *player_skills.skills.entry(skill.clone()).or_insert(0) += 1;
+    ecs.fetch_mut::<GameLog>().entries.push(format!("Your {:?} skill improves.", skill));
+}
+
+/// Takes the player up a level once they've spent their points, recalculating (and refilling)
+/// their health and mana to match.
+pub fn finish_level_up(ecs: &mut World) {
+    let player_entity = ecs.fetch::<Entity>();
+    let attributes = ecs.read_storage::<Attributes>();
+    let mut pools = ecs.write_storage::<Pools>();
+    let attr = attributes.get(*player_entity).unwrap();
+    let stats = pools.get_mut(*player_entity).unwrap();
+    stats.level += 1;
+    stats.hit_points.max = player_hp_at_level(attr.fitness.base + attr.fitness.modifiers, stats.level);
+    stats.hit_points.current = stats.hit_points.max;
+    stats.mana.max = mana_at_level(attr.intelligence.base + attr.intelligence.modifiers, stats.level);
+    stats.mana.current = stats.mana.max;
+    ecs.fetch_mut::<GameLog>().entries.push(format!("Congratulations, you are now level {}!", stats.level));
+}
@@ -0,0 +1,147 @@
+use specs::prelude::*;
+use super::{
+    Map, Position, Pools, Name, RunState, SufferDamage, DamageType, Burning, Viewshed, TileType,
+    gamelog::GameLog, particle_system::ParticleBuilder, map::{tile_has_flag, tile_walkable},
+    cloud_system::add_cloud,
+};
+
+/// How long a flammable tile burns before it's left scorched.
+const BURN_TURNS: i32 = 4;
+/// How long fire with its own fuel (oil from a flask, say) lasts on ground which won't burn.
+const FLASH_TURNS: i32 = 2;
+/// How long a creature keeps burning once it has caught fire.
+pub const CREATURE_BURN_TURNS: i32 = 3;
+
+/// Sets a tile alight. Only flammable ground catches on its own, but a `fuelled` fire will burn
+/// briefly on anything that isn't wet or solid. Returns true if the tile is now burning.
+pub fn ignite(map: &mut Map, idx: usize, fuelled: bool) -> bool {
+    let tile = map.tiles[idx];
+    if map.fire.contains_key(&idx) { return true; }
+    if !tile_walkable(tile) || tile_has_flag(tile, "water") { return false; }
+    if tile_has_flag(tile, "flammable") {
+        map.fire.insert(idx, BURN_TURNS);
+    } else if fuelled {
+        map.fire.insert(idx, FLASH_TURNS);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// Spreads fire from burning tiles to flammable neighbours, sets fire to anyone standing in it,
+/// and hurts anyone who is burning. Burnt out ground is left scorched, and fires give off smoke
+/// which blocks line of sight for a few turns. The fire moves once a turn, along with the monsters.
+pub struct FireSystem {}
+
+impl<'a> System<'a> for FireSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Burning>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Viewshed>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities, mut map, player_entity, runstate, positions, pools, names, mut burning,
+            mut inflict_damage, mut viewsheds, mut gamelog, mut particle_builder, mut rng,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn { return; }
+        if map.fire.is_empty() && burning.is_empty() { return; }
+
+        // Anyone standing in the flames catches fire
+        let mut fires: Vec<(usize, i32)> = map.fire.iter().map(|(idx, turns)| (*idx, *turns)).collect();
+        fires.sort_unstable(); // HashMap order isn't stable, and the dice rolls below should be repeatable
+        for (idx, _turns) in fires.iter() {
+            for victim in map.tile_content[*idx].iter() {
+                if pools.get(*victim).is_none() { continue; }
+                if burning.get(*victim).is_none() && (*victim == *player_entity || map.visible_tiles[*idx]) {
+                    match names.get(*victim) {
+                        Some(name) if *victim != *player_entity => gamelog.entries.push(format!("{} catches fire!", name.name)),
+                        _ => gamelog.entries.push("You catch fire!".to_string()),
+                    }
+                }
+                burning.insert(*victim, Burning{ turns: CREATURE_BURN_TURNS }).expect("Unable to insert burning");
+            }
+        }
+
+        // Burning creatures get hurt, and set fire to whatever they're standing on
+        let mut extinguished: Vec<Entity> = Vec::new();
+        for (entity, burns, pos) in (&entities, &mut burning, &positions).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            let is_player = entity == *player_entity;
+            let seen = is_player || map.visible_tiles[idx];
+            if tile_has_flag(map.tiles[idx], "water") {
+                extinguished.push(entity);
+                if seen {
+                    match names.get(entity) {
+                        Some(name) if !is_player => gamelog.entries.push(format!("The water puts out {}.", name.name)),
+                        _ => gamelog.entries.push("The water puts out the flames.".to_string()),
+                    }
+                }
+                continue;
+            }
+
+            SufferDamage::new_damage(&mut inflict_damage, entity, rng.roll_dice(1, 4), DamageType::Fire, false);
+            particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('^'), 200.0);
+            if is_player {
+                gamelog.entries.push("You are burning!".to_string());
+            }
+            ignite(&mut map, idx, false);
+
+            burns.turns -= 1;
+            if burns.turns < 1 {
+                extinguished.push(entity);
+            }
+        }
+        for entity in extinguished.iter() {
+            burning.remove(*entity);
+        }
+
+        // Fires spread, smoke, and eventually burn out
+        let mut spread_to: Vec<usize> = Vec::new();
+        for (idx, turns) in fires.iter() {
+            let (x, y) = (*idx as i32 % map.width, *idx as i32 / map.width);
+            let neighbours: Vec<usize> = [(x-1, y), (x+1, y), (x, y-1), (x, y+1)].iter()
+                .filter(|(nx, ny)| *nx >= 0 && *nx < map.width && *ny >= 0 && *ny < map.height)
+                .map(|(nx, ny)| map.xy_idx(*nx, *ny))
+                .collect();
+            for neighbour in neighbours.iter() {
+                if tile_has_flag(map.tiles[*neighbour], "flammable") && rng.roll_dice(1, 3) == 1 {
+                    spread_to.push(*neighbour);
+                }
+            }
+
+            let drift = neighbours[(rng.roll_dice(1, neighbours.len() as i32) - 1) as usize];
+            add_cloud(&mut map, drift, "Smoke");
+
+            if *turns > 1 {
+                map.fire.insert(*idx, turns - 1);
+            } else {
+                map.fire.remove(idx);
+                if tile_has_flag(map.tiles[*idx], "flammable") {
+                    map.tiles[*idx] = TileType::Scorched;
+                }
+            }
+        }
+        for idx in spread_to.iter() {
+            ignite(&mut map, *idx, false);
+        }
+
+        // The fire has moved, so everyone needs to take another look
+        for viewshed in (&mut viewsheds).join() {
+            viewshed.dirty = true;
+        }
+    }
+}
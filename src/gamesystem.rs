@@ -1,4 +1,5 @@
-use super::{Skill, Skills};
+use specs::prelude::*;
+use super::{Skill, Skills, Attributes, EncumbranceState, gamelog::GameLog};
 
 use regex::Regex;
 
@@ -54,6 +55,38 @@ pub fn mana_at_level(intelligence: i32, level: i32) -> i32 {
     mana_per_level(intelligence) * level
 }
 
+/// The total experience needed to advance past `level`.
+pub fn xp_to_next_level(level: i32) -> i32 {
+    level * 1000
+}
+
+/// Experience earned for killing something of the given level.
+pub fn xp_for_kill(victim_level: i32) -> i32 {
+    victim_level * 100
+}
+
+/// Experience earned for reaching a new depth of the dungeon.
+pub fn xp_for_depth(depth: i32) -> i32 {
+    depth * 50
+}
+
+/// How much weight (in pounds) someone can carry before they start to slow down.
+pub fn carry_capacity(might: i32) -> f32 {
+    (might * 5) as f32
+}
+
+/// How hampered someone is by carrying `weight`. Up to half as much again as they can
+/// comfortably carry just slows them a little; any more and they're overloaded.
+pub fn encumbrance_state(weight: f32, capacity: f32) -> EncumbranceState {
+    if weight <= capacity {
+        EncumbranceState::Unburdened
+    } else if weight <= capacity * 1.5 {
+        EncumbranceState::Burdened
+    } else {
+        EncumbranceState::Overloaded
+    }
+}
+
 pub fn skill_bonus(skill: Skill, skills: &Skills) -> i32 {
     if skills.skills.contains_key(&skill) {
         skills.skills[&skill]
@@ -61,3 +94,52 @@ pub fn skill_bonus(skill: Skill, skills: &Skills) -> i32 {
         -4
     }
 }
+
+/// How many successful uses it takes to raise a skill from `rank` to the next rank.
+pub fn practice_to_advance(rank: i32) -> i32 {
+    i32::max(1, rank + 1) * 10
+}
+
+/// Counts a successful use of a skill towards its next rank. Returns true if the skill went up.
+pub fn practice_skill(skill: Skill, skills: &mut Skills) -> bool {
+    let rank = skills.skills.get(&skill).copied().unwrap_or(0);
+    let practice = skills.practice.entry(skill.clone()).or_insert(0);
+    *practice += 1;
+    if *practice < practice_to_advance(rank) { return false; }
+    *practice = 0;
+    skills.skills.insert(skill, rank + 1);
+    true
+}
+
+/// Counts each of the player's successful uses towards the skill's next rank, telling them when it
+/// improves. Monsters don't learn from practice; their skills come from the raws.
+pub fn improve_skills(practised: &[(Entity, Skill)], skills: &mut WriteStorage<Skills>, player_entity: Entity, log: &mut GameLog) {
+    for (_entity, skill) in practised.iter().filter(|(entity, _skill)| *entity == player_entity) {
+        if let Some(player_skills) = skills.get_mut(player_entity) {
+            if practice_skill(skill.clone(), player_skills) {
+                log.entries.push(format!("Your {:?} skill improves!", skill));
+            }
+        }
+    }
+}
+
+/// Rolls a d20 perception check against a difficulty class, using the entity's intelligence and
+/// perception skill.
+pub fn perception_check(rng: &mut rltk::RandomNumberGenerator, attributes: &Attributes, skills: Option<&Skills>, difficulty: i32) -> bool {
+    let skill = skills.map_or(0, |s| skill_bonus(Skill::Perception, s));
+    rng.roll_dice(1, 20) + attributes.intelligence.bonus + skill >= difficulty
+}
+
+/// Rolls to avoid notice, on quickness and the stealth skill; heavy armour makes it harder.
+pub fn stealth_check(rng: &mut rltk::RandomNumberGenerator, attributes: &Attributes, skills: Option<&Skills>, armour_penalty: i32, difficulty: i32) -> bool {
+    let skill = skills.map_or(0, |s| skill_bonus(Skill::Stealth, s));
+    rng.roll_dice(1, 20) + attributes.quickness.bonus + skill - armour_penalty >= difficulty
+}
+
+/// How much easier it is to spot something hidden if it's a trap and you know about traps.
+pub fn trap_spotting_bonus(is_trap: bool, skills: Option<&Skills>) -> i32 {
+    match (is_trap, skills) {
+        (true, Some(skills)) => i32::max(0, skill_bonus(Skill::Traps, skills)),
+        _ => 0,
+    }
+}
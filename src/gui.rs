@@ -3,9 +3,20 @@ use specs::prelude::*;
 use super::{
     Pools, GameLog, Name, Map, Position, State, InBackpack,
     Viewshed, RunState, Equipped, HungerClock, HungerState, Hidden,
-    rex_assets::RexAssets, camera, Attributes, Attribute, Consumable
+    rex_assets::RexAssets, camera, Attributes, Attribute, Consumable, Ammunition,
+    StatusEffects, StatusEffect, Skills, xp_to_next_level, practice_to_advance,
+    experience::{LEVEL_UP_ATTRIBUTES, LEVEL_UP_SKILLS}, KnownSpells, Encumbrance,
+    EncumbranceState, Awareness, Alertness,
 };
 
+/// The name to show for something in the player's pack, with a count if it's a bundle of ammunition.
+fn carried_item_name(ecs: &World, item: Entity, name: &Name) -> String {
+    match ecs.read_storage::<Ammunition>().get(item) {
+        None => name.name.to_string(),
+        Some(ammo) => format!("{} ({})", name.name, ammo.count),
+    }
+}
+
 pub fn draw_hollow_box(
     console: &mut Rltk,
     sx: i32,
@@ -69,6 +80,8 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     ctx.print_color(50, 2, white, black, &mana);
     ctx.draw_bar_horizontal(64, 1, 14, player_pools.hit_points.current, player_pools.hit_points.max, RGB::named(rltk::RED), RGB::named(rltk::BLACK));
     ctx.draw_bar_horizontal(64, 2, 14, player_pools.mana.current, player_pools.mana.max, RGB::named(rltk::BLUE), RGB::named(rltk::BLACK));
+    let level = format!("Level: {}  XP: {}/{}", player_pools.level, player_pools.xp, xp_to_next_level(player_pools.level));
+    ctx.print_color(50, 3, white, black, &level);
 
     // Attributes
     let attributes = ecs.read_storage::<Attributes>();
@@ -89,6 +102,20 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         }
     }
 
+    // Skills, and how close each is to its next rank
+    y += 1;
+    let attr_grey: RGB = RGB::from_hex("#CCCCCC").expect("Oops");
+    let skills = ecs.read_storage::<Skills>();
+    let player_skills = skills.get(*player_entity).unwrap();
+    for skill in LEVEL_UP_SKILLS.iter() {
+        let rank = player_skills.skills.get(skill).copied().unwrap_or(0);
+        let practice = player_skills.practice.get(skill).copied().unwrap_or(0);
+        ctx.print_color(50, y, attr_grey, black, format!("{:?}", skill));
+        ctx.print_color(67, y, white, black, format!("{}", rank));
+        ctx.print_color(73, y, attr_grey, black, format!("{}%", practice * 100 / practice_to_advance(rank)));
+        y += 1;
+    }
+
     // Consumables
     y += 1;
     let green = RGB::from_f32(0.0, 1.0, 0.0);
@@ -114,6 +141,27 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         HungerState::Hungry => ctx.print_color(50, 44, RGB::named(rltk::ORANGE), black, "Hungry"),
         HungerState::Starving => ctx.print_color(50, 44, RGB::named(rltk::RED), black, "Starving"),
     }
+    let encumbrance = ecs.read_storage::<Encumbrance>();
+    match encumbrance.get(*player_entity).map(|burden| burden.state) {
+        Some(EncumbranceState::Burdened) => ctx.print_color(61, 44, RGB::named(rltk::ORANGE), black, "Burdened"),
+        Some(EncumbranceState::Overloaded) => ctx.print_color(61, 44, RGB::named(rltk::RED), black, "Overloaded"),
+        _ => {}
+    }
+    let status_effects = ecs.read_storage::<StatusEffects>();
+    if let Some(status) = status_effects.get(*player_entity) {
+        // Listed under the consumables, stopping short of the hunger and encumbrance line
+        y += 1;
+        for active in status.effects.iter() {
+            if y > 43 { break; }
+            let color = match active.effect {
+                StatusEffect::Regeneration | StatusEffect::Haste => green,
+                StatusEffect::Poison => RGB::named(rltk::RED),
+                _ => RGB::named(rltk::MAGENTA),
+            };
+            ctx.print_color(50, y, color, black, format!("{} ({})", active.effect.describe(), active.turns));
+            y += 1;
+        }
+    }
 
     // Draw log
     let log = ecs.fetch::<GameLog>();
@@ -185,16 +233,18 @@ impl Tooltip {
 
 fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     use rltk::to_cp437;
-    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs, ctx);
+    let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(ecs, ctx);
     let map = ecs.fetch::<Map>();
     let names = ecs.read_storage::<Name>();
     let positions = ecs.read_storage::<Position>();
     let hidden = ecs.read_storage::<Hidden>();
     let attributes = ecs.read_storage::<Attributes>();
     let pools = ecs.read_storage::<Pools>();
+    let awareness = ecs.read_storage::<Awareness>();
     let entities = ecs.entities();
 
     let mouse_pos = ctx.mouse_pos();
+    if mouse_pos.0 >= max_x - min_x || mouse_pos.1 >= max_y - min_y { return; } // Not over the map view
     let mut target_map_pos = mouse_pos;
     target_map_pos.0 += min_x;
     target_map_pos.1 += min_y;
@@ -231,6 +281,13 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                 tip.add(s);
             }
 
+            // Does it know you're there?
+            match awareness.get(entity).map(|a| a.state) {
+                Some(Alertness::Asleep) => tip.add("Asleep".to_string()),
+                Some(Alertness::Unaware) => tip.add("Unaware".to_string()),
+                _ => {}
+            }
+
             // Comment on pools
             let stat = pools.get(entity);
             if let Some(stat) = stat {
@@ -302,6 +359,18 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     ctx.print_color(18, y-2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Inventory");
     ctx.print_color(18, y+count as i32+1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "ESCAPE to cancel");
 
+    // How much the player is carrying, including what they're wearing
+    let encumbrance = gs.ecs.read_storage::<Encumbrance>();
+    if let Some(burden) = encumbrance.get(*player_entity) {
+        let weight = format!("{:.1}/{} lbs", burden.weight, burden.capacity);
+        let color = match burden.state {
+            EncumbranceState::Unburdened => RGB::named(rltk::WHITE),
+            EncumbranceState::Burdened => RGB::named(rltk::ORANGE),
+            EncumbranceState::Overloaded => RGB::named(rltk::RED),
+        };
+        ctx.print_color(45 - weight.len() as i32, y-2, color, RGB::named(rltk::BLACK), &weight);
+    }
+
     let mut usable: Vec<Entity> = Vec::new();
     let mut j = 0;
     for (entity, _pack, name) in (&entities, &backpack, &names).join().filter(|item| item.1.owner == *player_entity) {
@@ -309,7 +378,7 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         ctx.set(18, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97+j as rltk::FontCharType);
         ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(21, y, carried_item_name(&gs.ecs, entity, name));
         usable.push(entity);
         y += 1;
         j += 1;
@@ -353,7 +422,7 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         ctx.set(18, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97+j as rltk::FontCharType);
         ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(21, y, carried_item_name(&gs.ecs, entity, name));
         droppable.push(entity);
         y += 1;
         j += 1;
@@ -483,6 +552,99 @@ pub fn ranged_target(gs: &mut State, ctx: &mut Rltk, range: i32) -> (ItemMenuRes
     (ItemMenuResult::NoResponse, None)
 }
 
+// Spell menu
+/// Lists the spells the player knows, with their mana costs. Spells the player can't afford are
+/// greyed out, and can't be chosen. Returns the name of the chosen spell.
+pub fn show_spells(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<String>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let known_spells = gs.ecs.read_storage::<KnownSpells>();
+    let pools = gs.ecs.read_storage::<Pools>();
+    let spells = known_spells.get(*player_entity).map_or(Vec::new(), |known| known.spells.clone());
+    let mana = pools.get(*player_entity).map_or(0, |stats| stats.mana.current);
+    let costs: Vec<i32> = {
+        let raws = crate::raws::RAWS.lock().unwrap();
+        spells.iter().map(|spell| crate::raws::spell_mana_cost(&raws, spell).unwrap_or(0)).collect()
+    };
+    let count = spells.len();
+
+    let top = (25 - (count / 2)) as i32;
+    ctx.draw_box(15, top-2, 31, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    ctx.print_color(18, top-2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Cast which spell?");
+    ctx.print_color(18, top+count as i32+1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "ESCAPE to cancel");
+
+    for (j, (spell, cost)) in spells.iter().zip(costs.iter()).enumerate() {
+        let y = top + j as i32;
+        let color = if *cost > mana { RGB::named(rltk::GREY) } else { RGB::named(rltk::WHITE) };
+        ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
+        ctx.set(18, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97+j as rltk::FontCharType);
+        ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+        ctx.print_color(21, y, color, RGB::named(rltk::BLACK), format!("{} ({} mana)", spell, cost));
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => {
+            match key {
+                VirtualKeyCode::Escape => { (ItemMenuResult::Cancel, None) }
+                _ => {
+                    let selection = rltk::letter_to_option(key);
+                    if selection > -1 && selection < count as i32 && costs[selection as usize] <= mana {
+                        return (ItemMenuResult::Selected, Some(spells[selection as usize].clone()));
+                    }
+                    (ItemMenuResult::NoResponse, None)
+                }
+            }
+        }
+    }
+}
+
+// Level up menu
+/// Lets a player who's gone up a level pick an attribute to raise, and then a skill to train.
+/// Returns the index of their choice in `LEVEL_UP_ATTRIBUTES` or `LEVEL_UP_SKILLS`.
+pub fn level_up_menu(gs: &mut State, ctx: &mut Rltk, choosing_skill: bool) -> (ItemMenuResult, Option<usize>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let attributes = gs.ecs.read_storage::<Attributes>();
+    let skills = gs.ecs.read_storage::<Skills>();
+    let attr = attributes.get(*player_entity).unwrap();
+    let player_skills = skills.get(*player_entity).unwrap();
+
+    let options: Vec<String> = if choosing_skill {
+        LEVEL_UP_SKILLS.iter()
+            .map(|skill| format!("{:?} ({})", skill, player_skills.skills.get(skill).copied().unwrap_or(0)))
+            .collect()
+    } else {
+        [&attr.might, &attr.fitness, &attr.quickness, &attr.intelligence].iter().zip(LEVEL_UP_ATTRIBUTES.iter())
+            .map(|(attribute, name)| format!("{} ({})", name, attribute.base + attribute.modifiers))
+            .collect()
+    };
+    let count = options.len();
+
+    let top = (25 - (count / 2)) as i32;
+    ctx.draw_box(15, top-2, 31, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    ctx.print_color(18, top-2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Level Up!");
+    let prompt = if choosing_skill { "Choose a skill to train" } else { "Choose an attribute to raise" };
+    ctx.print_color(18, top+count as i32+1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), prompt);
+
+    for (j, option) in options.iter().enumerate() {
+        let y = top + j as i32;
+        ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
+        ctx.set(18, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97+j as rltk::FontCharType);
+        ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+        ctx.print(21, y, option);
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < count as i32 {
+                return (ItemMenuResult::Selected, Some(selection as usize));
+            }
+            (ItemMenuResult::NoResponse, None)
+        }
+    }
+}
+
 // Main menu code
 #[derive(PartialEq, Copy, Clone)]
 pub enum MainMenuSelection { NewGame, LoadGame, Quit }
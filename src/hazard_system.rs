@@ -0,0 +1,151 @@
+use specs::prelude::*;
+use super::{
+    Map, Position, Pools, Name, RunState, SufferDamage, DamageType, Swimming, Equipped, Wearable,
+    InBackpack, Attributes, Item, gamelog::GameLog, particle_system::ParticleBuilder,
+    map::tile_has_flag,
+};
+
+/// Worn gear at least this heavy (in pounds) drags a swimmer under, so it has to go.
+const HEAVY_ARMOUR: f32 = 10.0;
+/// How many turns anyone can tread water before they start to drown, before their fitness counts.
+const BASE_BREATH: i32 = 5;
+
+/// Returns the name to use when describing something which has happened to `victim`, which is
+/// None for the player, and whether the player saw it happen at `idx`. Only what happens to other
+/// creatures the player can see is worth mentioning.
+pub fn witness(victim: Entity, player: Entity, names: &ReadStorage<Name>, map: &Map, idx: usize) -> (Option<String>, bool) {
+    let is_player = victim == player;
+    let name = if is_player { None } else { names.get(victim).map(|n| n.name.clone()) };
+    (name, is_player || map.visible_tiles[idx])
+}
+
+/// Removes a creature which has left the level for good, along with everything it was carrying.
+pub fn remove_with_belongings(entities: &Entities, victim: Entity, equipped: &ReadStorage<Equipped>, backpack: &ReadStorage<InBackpack>) {
+    let belongings: Vec<Entity> = (entities, equipped).join()
+        .filter(|(_item, eq)| eq.owner == victim)
+        .map(|(item, _eq)| item)
+        .chain((entities, backpack).join().filter(|(_item, pack)| pack.owner == victim).map(|(item, _pack)| item))
+        .collect();
+    for item in belongings.iter() {
+        entities.delete(*item).expect("Unable to delete");
+    }
+    entities.delete(victim).expect("Unable to delete");
+}
+
+/// Applies the effects of hazardous terrain to whoever is standing on it: lava burns, chasms
+/// swallow, and deep water strips off heavy armour and eventually drowns. Like hunger, the player
+/// is dealt with on their turn and everyone else on theirs.
+pub struct HazardSystem {}
+
+impl<'a> System<'a> for HazardSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, RunState>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Swimming>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Wearable>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Item>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities, map, player_entity, mut runstate, positions, pools, names, attributes,
+            mut inflict_damage, mut swimming, equipped, wearables, backpack, items, mut gamelog,
+            mut particle_builder, mut rng,
+        ) = data;
+
+        let mut fallen: Vec<Entity> = Vec::new();
+        let mut sunk: Vec<Entity> = Vec::new();
+
+        for (entity, pos, _pools) in (&entities, &positions, &pools).join() {
+            let is_player = entity == *player_entity;
+            let proceed = match *runstate {
+                RunState::PlayerTurn => is_player,
+                RunState::MonsterTurn => !is_player,
+                _ => false
+            };
+            if !proceed { continue; }
+
+            let idx = map.xy_idx(pos.x, pos.y);
+            let tile = map.tiles[idx];
+            let (name, seen) = witness(entity, *player_entity, &names, &map, idx);
+
+            if tile_has_flag(tile, "burns") {
+                SufferDamage::new_damage(&mut inflict_damage, entity, rng.roll_dice(2, 6), DamageType::Fire, false);
+                particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('▲'), 200.0);
+                if seen {
+                    match &name {
+                        None => gamelog.entries.push("The lava burns you!".to_string()),
+                        Some(name) => gamelog.entries.push(format!("{} is burned by the lava!", name)),
+                    }
+                }
+            }
+
+            if tile_has_flag(tile, "chasm") {
+                if is_player {
+                    gamelog.entries.push("You fall into the chasm!".to_string());
+                    SufferDamage::new_damage(&mut inflict_damage, entity, rng.roll_dice(1, 6), DamageType::Bludgeoning, false);
+                    *runstate = RunState::NextLevel;
+                } else {
+                    if let (true, Some(name)) = (seen, &name) {
+                        gamelog.entries.push(format!("{} falls into the chasm!", name));
+                    }
+                    fallen.push(entity);
+                }
+                continue;
+            }
+
+            if tile_has_flag(tile, "swim") {
+                // Nobody can swim in heavy armour; the heaviest piece goes first
+                let heaviest = (&entities, &equipped, &wearables, &items).join()
+                    .filter(|(_item, eq, _wearable, item)| eq.owner == entity && item.weight >= HEAVY_ARMOUR)
+                    .max_by(|a, b| a.3.weight.partial_cmp(&b.3.weight).unwrap())
+                    .map(|(item, _eq, _wearable, _item)| item);
+                if let Some(item) = heaviest {
+                    sunk.push(item);
+                    if let (true, Some(item_name)) = (seen, names.get(item)) {
+                        match &name {
+                            None => gamelog.entries.push(format!("Your {} drags you under, and you let it sink.", item_name.name)),
+                            Some(name) => gamelog.entries.push(format!("{} sheds its {}.", name, item_name.name)),
+                        }
+                    }
+                }
+
+                let turns = swimming.get(entity).map_or(1, |s| s.turns + 1);
+                swimming.insert(entity, Swimming{ turns }).expect("Unable to insert swimming");
+                let breath = BASE_BREATH + attributes.get(entity).map_or(0, |a| a.fitness.bonus);
+                if turns > breath {
+                    SufferDamage::new_damage(&mut inflict_damage, entity, rng.roll_dice(1, 4), DamageType::Untyped, false);
+                    if seen {
+                        match &name {
+                            None => gamelog.entries.push("You are drowning!".to_string()),
+                            Some(name) => gamelog.entries.push(format!("{} is drowning!", name)),
+                        }
+                    }
+                }
+            } else {
+                swimming.remove(entity);
+            }
+        }
+
+        // Whatever falls into a chasm is gone for good
+        for victim in fallen.iter() {
+            remove_with_belongings(&entities, *victim, &equipped, &backpack);
+        }
+        for item in sunk.iter() {
+            entities.delete(*item).expect("Unable to delete");
+        }
+    }
+}
@@ -3,9 +3,11 @@ use super::{
     WantsToPickupItem, Name, InBackpack, Position, gamelog::GameLog,
     ProvidesHealing, Pools, WantsToUseItem, WantsToDropItem,
     Consumable, InflictsDamage, Map, SufferDamage, AreaOfEffect,
-    Stunned, Equippable, Equipped, WantsToRemoveItem,
+    StatusEffects, InflictsStatus, Equippable, Equipped, EquipmentSlot, WantsToRemoveItem,
     particle_system::ParticleBuilder, ProvidesFood, HungerClock,
-    HungerState, MagicMapper, RunState,
+    HungerState, MagicMapper, RunState, StartsFire, Burning, fire_system::{ignite, CREATURE_BURN_TURNS},
+    CreatesCloud, cloud_system::add_cloud, Spell, Skills, Skill, skill_bonus, TeachesSpell, KnownSpells,
+    Entangles, StatusEffect, Charges, Summons, WantsToSummon, Viewshed, EntityMoved, Ammunition,
 };
 
 pub struct ItemCollectionSystem {}
@@ -14,19 +16,42 @@ impl<'a> System<'a> for ItemCollectionSystem {
     type SystemData = (
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
+        Entities<'a>,
         WriteStorage<'a, WantsToPickupItem>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Ammunition>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) = data;
+        let (player_entity, mut gamelog, entities, mut wants_pickup, mut positions, names, mut backpack, mut ammunition) = data;
 
         for pickup in wants_pickup.join() {
             // Iterate through all entities which want to pick something up.
             positions.remove(pickup.item); // Remove the item from the gameworld
-            backpack.insert(pickup.item, InBackpack{ owner: pickup.collected_by }).expect("Unable to insert backpack entry."); // Add to inventory
+
+            // Ammunition joins any matching bundle already in the pack, rather than taking up
+            // a slot of its own
+            let picked_ammo = ammunition.get(pickup.item).cloned();
+            let picked_name = names.get(pickup.item).map(|n| n.name.clone());
+            let stack = picked_ammo.as_ref().and_then(|picked| {
+                (&entities, &ammunition, &backpack, &names).join()
+                    .find(|(entity, ammo, pack, name)| *entity != pickup.item && pack.owner == pickup.collected_by
+                        && ammo.kind == picked.kind && Some(&name.name) == picked_name.as_ref())
+                    .map(|(entity, _ammo, _pack, _name)| entity)
+            });
+            match (stack, picked_ammo) {
+                (Some(stack), Some(picked)) => {
+                    if let Some(ammo) = ammunition.get_mut(stack) {
+                        ammo.count += picked.count;
+                    }
+                    entities.delete(pickup.item).expect("Unable to delete");
+                }
+                _ => {
+                    backpack.insert(pickup.item, InBackpack{ owner: pickup.collected_by }).expect("Unable to insert backpack entry."); // Add to inventory
+                }
+            }
 
             if pickup.collected_by == *player_entity {
                 // If picked up by player, log
@@ -44,7 +69,7 @@ impl<'a> System<'a> for ItemUseSystem {
     type SystemData = (
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
-        ReadExpect<'a, Map>,
+        WriteExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, WantsToUseItem>,
         ReadStorage<'a, Name>,
@@ -54,41 +79,68 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, Pools>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Stunned>,
+        WriteStorage<'a, StatusEffects>,
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
         WriteExpect<'a, ParticleBuilder>,
-        ReadStorage<'a, Position>,
+        WriteStorage<'a, Position>,
         ReadStorage<'a, ProvidesFood>,
         WriteStorage<'a, HungerClock>,
         ReadStorage<'a, MagicMapper>,
         WriteExpect<'a, RunState>,
+        ReadStorage<'a, StartsFire>,
+        WriteStorage<'a, Burning>,
+        (
+            ReadStorage<'a, CreatesCloud>,
+            ReadStorage<'a, InflictsStatus>,
+            ReadStorage<'a, Spell>,
+            ReadStorage<'a, Skills>,
+            ReadStorage<'a, TeachesSpell>,
+            WriteStorage<'a, KnownSpells>,
+        ),
+        (
+            ReadStorage<'a, Entangles>,
+            ReadStorage<'a, Charges>,
+            ReadStorage<'a, Summons>,
+            WriteStorage<'a, WantsToSummon>,
+            WriteStorage<'a, Viewshed>,
+            WriteStorage<'a, EntityMoved>,
+        ),
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
-            player_entity, mut gamelog, map, entities, mut wants_use, names,
+            player_entity, mut gamelog, mut map, entities, mut wants_use, names,
             consumables, healing, inflict_damage, mut combat_stats,
-            mut suffer_damage, aoe, mut stunned, equippable, mut equipped,
-            mut backpack, mut particle_builder, positions, provides_food,
-            mut hungerclocks, magic_mapper, mut runstate,
+            mut suffer_damage, aoe, mut status_effects, equippable, mut equipped,
+            mut backpack, mut particle_builder, mut positions, provides_food,
+            mut hungerclocks, magic_mapper, mut runstate, starts_fire, mut burning,
+            (creates_cloud, inflicts_status, spells, skills, teaches_spell, mut known_spells),
+            (entangles, charges, summons, mut wants_summon, mut viewsheds, mut entity_moved),
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
             let mut used_item = true;
             let mut _used_item = true;
 
+            // Spells are cast rather than used, and get stronger with the caster's magic skill
+            let is_spell = spells.get(useitem.item).is_some();
+            let verb = if is_spell { "cast" } else { "use" };
+            let spell_power = if is_spell { skills.get(entity).map_or(0, |s| i32::max(0, skill_bonus(Skill::Magic, s))) } else { 0 };
+
             // Targeting
             let mut targets: Vec<Entity> = Vec::new();
+            let mut target_tiles: Vec<usize> = Vec::new();
             match useitem.target {
-                None => { targets.push( *player_entity ); }
+                None => { targets.push( entity ); }
                 Some(target) => {
                     let area_effect = aoe.get(useitem.item);
                     match area_effect {
                         None => {
                             // Single target in the tile
                             let idx = map.xy_idx(target.x, target.y);
+                            target_tiles.push(idx);
                             for mob in map.tile_content[idx].iter() {
                                 targets.push(*mob);
                             }
@@ -99,6 +151,7 @@ impl<'a> System<'a> for ItemUseSystem {
                             blast_tiles.retain(|p| p.x > 0 && p.x < map.width-1 && p.y > 0 && p.y < map.height-1);
                             for tile_idx in blast_tiles.iter() {
                                 let idx = map.xy_idx(tile_idx.x, tile_idx.y);
+                                target_tiles.push(idx);
                                 for mob in map.tile_content[idx].iter() {
                                     targets.push(*mob);
                                 }
@@ -110,6 +163,7 @@ impl<'a> System<'a> for ItemUseSystem {
             }
 
             // If item is equippable, equip it, and unequip whatever else was in that slot.
+            // Two-handed weapons and shields can't be used together, so one pushes out the other.
             let item_equippable = equippable.get(useitem.item);
             match item_equippable {
                 None => {}
@@ -117,10 +171,12 @@ impl<'a> System<'a> for ItemUseSystem {
                     let target_slot = can_equip.slot;
                     let target = targets[0];
 
-                    // Remove any items the target has in the item's slot
+                    // Remove any items the target has in the item's slot, or which need the hand it takes
                     let mut to_unequip: Vec<Entity> = Vec::new();
-                    for (item_entity, already_equipped, name) in (&entities, &equipped, &names).join() {
-                        if already_equipped.owner == target && already_equipped.slot == target_slot {
+                    for (item_entity, already_equipped, name, worn) in (&entities, &equipped, &names, &equippable).join() {
+                        let hands_clash = (target_slot == EquipmentSlot::Shield && worn.hands > 1)
+                            || (can_equip.hands > 1 && already_equipped.slot == EquipmentSlot::Shield);
+                        if already_equipped.owner == target && (already_equipped.slot == target_slot || hands_clash) {
                             to_unequip.push(item_entity);
                             if target == *player_entity {
                                 gamelog.entries.push(format!("You unequip the {}.", name.name));
@@ -145,12 +201,13 @@ impl<'a> System<'a> for ItemUseSystem {
             match item_heals {
                 None => {}
                 Some(healer) => {
+                    let heal_amount = healer.heal_amount + spell_power;
                     for target in targets.iter() {
                         let stats = combat_stats.get_mut(*target);
                         if let Some(stats) = stats {
-                            stats.hit_points.current = i32::min(stats.hit_points.max, stats.hit_points.current + healer.heal_amount);
+                            stats.hit_points.current = i32::min(stats.hit_points.max, stats.hit_points.current + heal_amount);
                             if entity == *player_entity {
-                                gamelog.entries.push(format!("You use the {}, healing {} hp.", names.get(useitem.item).unwrap().name, healer.heal_amount));
+                                gamelog.entries.push(format!("You {} the {}, healing {} hp.", verb, names.get(useitem.item).unwrap().name, heal_amount));
                             }
                             _used_item = true;
 
@@ -188,31 +245,62 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
+            // Charging rushes the caster along the line to their target, stopping alongside them.
+            // If something's in the way, the charge falls short and hits nothing.
+            if let (Some(_charge), Some(target)) = (charges.get(useitem.item), useitem.target) {
+                if let Some(pos) = positions.get_mut(entity) {
+                    let start_idx = map.xy_idx(pos.x, pos.y);
+                    let mut end = rltk::Point::new(pos.x, pos.y);
+                    for step in rltk::line2d(rltk::LineAlg::Bresenham, end, target).iter().skip(1) {
+                        if rltk::DistanceAlg::Pythagoras.distance2d(end, target) < 1.5 { break; }
+                        if map.blocked[map.xy_idx(step.x, step.y)] { break; }
+                        end = *step;
+                    }
+                    if rltk::DistanceAlg::Pythagoras.distance2d(end, target) < 1.5 {
+                        map.blocked[start_idx] = false;
+                        pos.x = end.x;
+                        pos.y = end.y;
+                        let end_idx = map.xy_idx(end.x, end.y);
+                        map.blocked[end_idx] = true;
+                        entity_moved.insert(entity, EntityMoved{}).expect("Unable to insert marker");
+                        if let Some(viewshed) = viewsheds.get_mut(entity) {
+                            viewshed.dirty = true;
+                        }
+                    } else {
+                        targets.clear();
+                    }
+                }
+            }
+
             let item_damages = inflict_damage.get(useitem.item);
             match item_damages {
                 None => {}
                 Some(damage) => {
                     used_item = false;
+                    let amount = damage.damage + spell_power;
                     for mob in targets.iter() {
                         let damagable = combat_stats.get(*mob);
                         if let Some(_damagable) = damagable {
-                            SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                            SufferDamage::new_damage(&mut suffer_damage, *mob, amount, damage.damage_type, entity == *player_entity);
                             if entity == *player_entity {
                                 let mob_name = names.get(*mob).unwrap();
                                 let item_name = names.get(useitem.item).unwrap();
-                                gamelog.entries.push(format!("You use {} on {}, inflicting {} damage.", item_name.name, mob_name.name, damage.damage));
-
-                                let pos = positions.get(*mob);
-                                if let Some(pos) = pos {
-                                    particle_builder.request(
-                                        pos.x,
-                                        pos.y,
-                                        rltk::RGB::named(rltk::RED),
-                                        rltk::RGB::named(rltk::BLACK),
-                                        rltk::to_cp437('‼'),
-                                        200.0
-                                    );
-                                }
+                                gamelog.entries.push(format!("You {} {} on {}, inflicting {} damage.", verb, item_name.name, mob_name.name, amount));
+                            } else if *mob == *player_entity {
+                                let item_name = names.get(useitem.item).unwrap();
+                                gamelog.entries.push(format!("The {} hits you for {} damage!", item_name.name, amount));
+                            }
+
+                            let pos = positions.get(*mob);
+                            if let Some(pos) = pos {
+                                particle_builder.request(
+                                    pos.x,
+                                    pos.y,
+                                    rltk::RGB::named(rltk::RED),
+                                    rltk::RGB::named(rltk::BLACK),
+                                    rltk::to_cp437('‼'),
+                                    200.0
+                                );
                             }
 
                             used_item = true;
@@ -221,31 +309,79 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
-            let mut add_stun = Vec::new();
-            {
-                let causes_stun = stunned.get(useitem.item);
-                match causes_stun {
-                    None => {}
-                    Some(stunned) => {
-                        _used_item = false;
-                        for mob in targets.iter() {
-                            add_stun.push((*mob, stunned.turns));
-                            if entity == *player_entity {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(useitem.item).unwrap();
-                                gamelog.entries.push(format!("You use {} on {}, stunning them.", item_name.name, mob_name.name));
+            if starts_fire.get(useitem.item).is_some() {
+                used_item = true;
+                let mut lit = false;
+                for idx in target_tiles.iter() {
+                    lit |= ignite(&mut map, *idx, true);
+                }
+                for mob in targets.iter() {
+                    if combat_stats.get(*mob).is_some() {
+                        burning.insert(*mob, Burning{ turns: CREATURE_BURN_TURNS }).expect("Unable to insert burning");
+                    }
+                }
+                if lit && entity == *player_entity {
+                    gamelog.entries.push("Flames spring up!".to_string());
+                }
+            }
 
-                                let pos = positions.get(*mob);
-                                if let Some(pos) = pos {
-                                    particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::MAGENTA), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('?'), 200.0);
-                                }
-                            }
+            if let Some(creates) = creates_cloud.get(useitem.item) {
+                used_item = true;
+                for idx in target_tiles.iter() {
+                    add_cloud(&mut map, *idx, &creates.cloud);
+                }
+                if entity == *player_entity {
+                    gamelog.entries.push(format!("A cloud of {} billows out.", creates.cloud.to_lowercase()));
+                }
+            }
+
+            if let Some(inflicts) = inflicts_status.get(useitem.item) {
+                used_item = true;
+                for mob in targets.iter() {
+                    if combat_stats.get(*mob).is_none() { continue; }
+                    for (effect, turns) in inflicts.effects.iter() {
+                        StatusEffects::apply(&mut status_effects, *mob, *effect, *turns);
+                        if *mob == *player_entity {
+                            gamelog.entries.push(format!("You are {}!", effect.describe().to_lowercase()));
+                        } else if entity == *player_entity {
+                            let mob_name = names.get(*mob).unwrap();
+                            gamelog.entries.push(format!("{} is {}!", mob_name.name, effect.describe().to_lowercase()));
                         }
                     }
+                    if let (true, Some(pos)) = (entity == *player_entity, positions.get(*mob)) {
+                        particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::MAGENTA), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('?'), 200.0);
+                    }
+                }
+            }
+
+            if let Some(web) = entangles.get(useitem.item) {
+                used_item = true;
+                for mob in targets.iter() {
+                    if combat_stats.get(*mob).is_none() { continue; }
+                    StatusEffects::apply(&mut status_effects, *mob, StatusEffect::Entangled, web.turns);
+                    if *mob == *player_entity {
+                        gamelog.entries.push("You are stuck fast!".to_string());
+                    } else if entity == *player_entity {
+                        gamelog.entries.push(format!("{} is stuck fast!", names.get(*mob).unwrap().name));
+                    }
                 }
             }
-            for mob in add_stun.iter() {
-                stunned.insert(mob.0, Stunned{ turns: mob.1 }).expect("Unable to insert status.");
+
+            if let Some(summon) = summons.get(useitem.item) {
+                used_item = true;
+                wants_summon.insert(entity, WantsToSummon{ mob: summon.mob.clone(), count: summon.count }).expect("Unable to insert intent");
+            }
+
+            if let Some(book) = teaches_spell.get(useitem.item) {
+                used_item = true;
+                if let Some(known) = known_spells.get_mut(entity) {
+                    if known.spells.contains(&book.spell) {
+                        gamelog.entries.push(format!("You already know {}.", book.spell));
+                    } else {
+                        known.spells.push(book.spell.clone());
+                        gamelog.entries.push(format!("You learn to cast {}!", book.spell));
+                    }
+                }
             }
 
             let consumable = consumables.get(useitem.item);
@@ -257,6 +393,10 @@ impl<'a> System<'a> for ItemUseSystem {
                     }
                 }
             }
+            // A spell is gone once it's been cast, whatever it did
+            if is_spell {
+                entities.delete(useitem.item).expect("Delete failed!");
+            }
         }
 
         wants_use.clear();
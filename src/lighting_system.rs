@@ -0,0 +1,72 @@
+use specs::prelude::*;
+use super::{Map, Position, LightSource, Viewshed, Player};
+use rltk::{field_of_view, Point, RGB};
+
+/// Tiles with less light than this are too dark to see into.
+pub const DARKNESS: f32 = 0.2;
+/// How far away you can make things out without any light at all.
+pub const NIGHT_VISION: f32 = 1.5;
+/// How far the light from a burning tile reaches.
+const FIRE_LIGHT_RANGE: i32 = 4;
+
+/// Returns true if a tile is bright enough to see what's on it.
+pub fn is_lit(map: &Map, idx: usize) -> bool {
+    let light = map.light[idx];
+    f32::max(light.r, f32::max(light.g, light.b)) >= DARKNESS
+}
+
+/// Works out how brightly lit each tile is, from the level's ambient light plus any light sources.
+pub struct LightingSystem {}
+
+impl<'a> System<'a> for LightingSystem {
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, LightSource>,
+        WriteStorage<'a, Viewshed>,
+        ReadStorage<'a, Player>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut map, positions, light_sources, mut viewsheds, player) = data;
+
+        let ambient = RGB::from_f32(map.ambient_light, map.ambient_light, map.ambient_light);
+        let mut light = vec![ambient; map.tiles.len()];
+
+        // Everything which gives off light, including anything that's on fire
+        let mut sources: Vec<(Point, i32, RGB)> = (&positions, &light_sources).join()
+            .map(|(pos, light_source)| (Point::new(pos.x, pos.y), light_source.range, light_source.color))
+            .collect();
+        for idx in map.fire.keys() {
+            sources.push((Point::new(*idx as i32 % map.width, *idx as i32 / map.width), FIRE_LIGHT_RANGE, RGB::from_f32(1.0, 0.5, 0.1)));
+        }
+
+        for (center, range, color) in sources.iter() {
+            let lit_tiles = field_of_view(*center, *range, &*map);
+            for t in lit_tiles.iter() {
+                if t.x < 0 || t.x > map.width-1 || t.y < 0 || t.y > map.height-1 { continue; }
+                let idx = map.xy_idx(t.x, t.y);
+                // Light fades the further it gets from its source
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(*center, *t);
+                let intensity = (*range as f32 - distance) / *range as f32;
+                if intensity > 0.0 {
+                    light[idx] = light[idx] + (*color * intensity);
+                }
+            }
+        }
+
+        for l in light.iter_mut() {
+            l.r = f32::min(1.0, l.r);
+            l.g = f32::min(1.0, l.g);
+            l.b = f32::min(1.0, l.b);
+        }
+
+        // If the lighting has changed, the player may be able to see more (or less) than before
+        if light != map.light {
+            map.light = light;
+            for (viewshed, _player) in (&mut viewsheds, &player).join() {
+                viewshed.dirty = true;
+            }
+        }
+    }
+}
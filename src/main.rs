@@ -21,16 +21,13 @@ pub use options::OPTIONS;
 
 mod visibility_system;
 use visibility_system::VisibilitySystem;
-mod monster_ai_system;
-use monster_ai_system::MonsterAI;
-mod bystander_ai_system;
-use bystander_ai_system::BystanderAI;
-mod animal_ai_system;
-use animal_ai_system::AnimalAI;
+mod ai;
 mod map_indexing_system;
 use map_indexing_system::MapIndexingSystem;
 mod melee_combat_system;
+mod ranged_combat_system;
 use melee_combat_system::MeleeCombatSystem;
+use ranged_combat_system::RangedCombatSystem;
 mod damage_system;
 use damage_system::DamageSystem;
 mod gui;
@@ -47,6 +44,17 @@ pub mod random_table;
 mod particle_system;
 mod hunger_system;
 mod trigger_system;
+mod perception_system;
+mod lighting_system;
+mod hazard_system;
+mod fire_system;
+mod cloud_system;
+mod status_effect_system;
+mod spell_system;
+mod doors;
+mod experience;
+mod encumbrance_system;
+mod stealth_system;
 pub mod map_builders;
 mod gamesystem;
 pub use gamesystem::*;
@@ -56,12 +64,18 @@ pub use gamesystem::*;
 #[derive(PartialEq, Copy, Clone)]
 pub enum RunState { AwaitingInput, PreRun, PlayerTurn, MonsterTurn, ShowInventory, ShowDropItem,
     ShowTargeting { range: i32, item: Entity},
+    ShowFiring { range: i32 },
+    ShowSpells,
+    ShowCasting { range: i32, spell: Entity },
     MainMenu{ menu_selection: gui::MainMenuSelection },
     SaveGame,
     NextLevel,
     ShowRemoveItem,
     GameOver,
     MagicMapReveal{ row: i32 },
+    ShowCloseDoor,
+    ShowDisarmTrap,
+    LevelUp{ choosing_skill: bool },
     MapGeneration,
     Wait,
 }
@@ -76,33 +90,73 @@ pub struct State{
 
 impl State {
     fn run_systems(&mut self) {
+        let mut lighting = lighting_system::LightingSystem{};
+        lighting.run_now(&self.ecs);
+
         let mut vis = VisibilitySystem{};
         vis.run_now(&self.ecs);
 
+        let mut perception = perception_system::PerceptionSystem{};
+        perception.run_now(&self.ecs);
+        let mut stealth = stealth_system::StealthSystem{};
+        stealth.run_now(&self.ecs);
+
         let mut pickup = ItemCollectionSystem{};
         pickup.run_now(&self.ecs);
 
-        // AI systems
-        let mut mob = MonsterAI{};
-        mob.run_now(&self.ecs);
-        let mut bystander = BystanderAI{};
-        bystander.run_now(&self.ecs);
-        let mut animal = AnimalAI{};
-        animal.run_now(&self.ecs);
+        // AI systems: who gets a turn, what stops them using it, what they decide to do with it,
+        // and finally the moves they've decided on
+        let mut initiative = ai::InitiativeSystem{};
+        initiative.run_now(&self.ecs);
+        let mut turn_status = ai::TurnStatusSystem{};
+        turn_status.run_now(&self.ecs);
+        let mut factions = ai::FactionAI{};
+        factions.run_now(&self.ecs);
+        let mut quips = ai::QuipSystem{};
+        quips.run_now(&self.ecs);
+        let mut morale = ai::MoraleAI{};
+        morale.run_now(&self.ecs);
+        let mut reactions = ai::ReactionAI{};
+        reactions.run_now(&self.ecs);
+        let mut chase = ai::ChaseAI{};
+        chase.run_now(&self.ecs);
+        let mut wander = ai::WanderAI{};
+        wander.run_now(&self.ecs);
+        let mut movement = ai::MovementSystem{};
+        movement.run_now(&self.ecs);
 
         let mut triggers = trigger_system::TriggerSystem{};
         triggers.run_now(&self.ecs);
 
+        let mut hazards = hazard_system::HazardSystem{};
+        hazards.run_now(&self.ecs);
+        let mut status_effects = status_effect_system::StatusEffectSystem{};
+        status_effects.run_now(&self.ecs);
+
         let mut mapindex = MapIndexingSystem{};
         mapindex.run_now(&self.ecs);
 
+        let mut fire = fire_system::FireSystem{};
+        fire.run_now(&self.ecs);
+        let mut clouds = cloud_system::CloudSystem{};
+        clouds.run_now(&self.ecs);
+
         let mut melee = MeleeCombatSystem{};
         melee.run_now(&self.ecs);
+        let mut ranged = RangedCombatSystem{};
+        ranged.run_now(&self.ecs);
         let mut damage = DamageSystem{};
         damage.run_now(&self.ecs);
 
+        spell_system::ready_abilities(&mut self.ecs);
+        let mut spells = spell_system::SpellCastSystem{};
+        spells.run_now(&self.ecs);
+        let mut mana = spell_system::ManaRegenSystem{};
+        mana.run_now(&self.ecs);
+
         let mut potions = ItemUseSystem{};
         potions.run_now(&self.ecs);
+        spell_system::summon_allies(&mut self.ecs);
 
         let mut drop_items = ItemDropSystem{};
         drop_items.run_now(&self.ecs);
@@ -110,6 +164,9 @@ impl State {
         let mut item_remove = ItemRemoveSystem{};
         item_remove.run_now(&self.ecs);
 
+        let mut encumbrance = encumbrance_system::EncumbranceSystem{};
+        encumbrance.run_now(&self.ecs);
+
         let mut hunger = hunger_system::HungerSystem{};
         hunger.run_now(&self.ecs);
 
@@ -202,12 +259,28 @@ impl GameState for State {
             RunState::AwaitingInput => {
                 newrunstate = player_input(self, ctx)
             }
+            RunState::ShowCloseDoor => {
+                newrunstate = close_door_input(self, ctx)
+            }
+            RunState::ShowDisarmTrap => {
+                newrunstate = disarm_trap_input(self, ctx)
+            }
             RunState::PlayerTurn => {
                 self.run_systems();
                 self.ecs.maintain();
-                match *self.ecs.fetch::<RunState>() {
+                let runstate = *self.ecs.fetch::<RunState>();
+                match runstate {
                     RunState::MagicMapReveal{ .. } => newrunstate = RunState::MagicMapReveal{ row: 0 },
-                    _ => newrunstate = RunState::MonsterTurn
+                    RunState::NextLevel => newrunstate = RunState::NextLevel,
+                    _ if experience::ready_to_level(&self.ecs) => newrunstate = RunState::LevelUp{ choosing_skill: false },
+                    _ => {
+                        // A hasted player sometimes gets to act again before the monsters do
+                        newrunstate = if status_effect_system::hasted_extra_action(&mut self.ecs) {
+                            RunState::AwaitingInput
+                        } else {
+                            RunState::MonsterTurn
+                        };
+                    }
                 }
             }
             RunState::MonsterTurn => {
@@ -215,6 +288,24 @@ impl GameState for State {
                 self.ecs.maintain();
                 newrunstate = RunState::AwaitingInput;
             }
+            RunState::LevelUp{ choosing_skill } => {
+                let result = gui::level_up_menu(self, ctx, choosing_skill);
+                if let (gui::ItemMenuResult::Selected, Some(choice)) = result {
+                    if choosing_skill {
+                        experience::train_skill(&mut self.ecs, experience::LEVEL_UP_SKILLS[choice].clone());
+                        experience::finish_level_up(&mut self.ecs);
+                        // Enough experience might have been earned to go up more than one level
+                        newrunstate = if experience::ready_to_level(&self.ecs) {
+                            RunState::LevelUp{ choosing_skill: false }
+                        } else {
+                            RunState::MonsterTurn
+                        };
+                    } else {
+                        experience::raise_attribute(&mut self.ecs, choice);
+                        newrunstate = RunState::LevelUp{ choosing_skill: true };
+                    }
+                }
+            }
             RunState::ShowInventory => {
                 let result = gui::show_inventory(self, ctx);
                 match result.0 {
@@ -226,10 +317,12 @@ impl GameState for State {
                         let is_item_ranged = is_ranged.get(item_entity);
                         if let Some(is_item_ranged) = is_item_ranged {
                             newrunstate = RunState::ShowTargeting{ range: is_item_ranged.range, item: item_entity };
-                        } else {
+                        } else if !loses_turn(&self.ecs) {
                             let mut intent = self.ecs.write_storage::<WantsToUseItem>();
                             intent.insert(*self.ecs.fetch::<Entity>(), WantsToUseItem{ item: item_entity, target: None }).expect("Unable to insert intent!");
                             newrunstate = RunState::PlayerTurn;
+                        } else {
+                            newrunstate = RunState::PlayerTurn;
                         }
                     }
                 }
@@ -239,6 +332,7 @@ impl GameState for State {
                 match result.0 {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {},
+                    gui::ItemMenuResult::Selected if loses_turn(&self.ecs) => newrunstate = RunState::PlayerTurn,
                     gui::ItemMenuResult::Selected => {
                         let item_entity = result.1.unwrap();
                         let mut intent = self.ecs.write_storage::<WantsToDropItem>();
@@ -252,6 +346,7 @@ impl GameState for State {
                 match result.0 {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected if loses_turn(&self.ecs) => newrunstate = RunState::PlayerTurn,
                     gui::ItemMenuResult::Selected => {
                         let mut intent = self.ecs.write_storage::<WantsToUseItem>();
                         intent.insert(*self.ecs.fetch::<Entity>(), WantsToUseItem{ item, target: result.1 }).expect("Unable to insert intent!");
@@ -259,6 +354,81 @@ impl GameState for State {
                     }
                 }
             }
+            RunState::ShowFiring{range} => {
+                let result = gui::ranged_target(self, ctx, range);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let target = result.1.unwrap();
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        let target_entity = {
+                            let map = self.ecs.fetch::<Map>();
+                            let pools = self.ecs.read_storage::<Pools>();
+                            let idx = map.xy_idx(target.x, target.y);
+                            map.tile_content[idx].iter().find(|e| **e != player_entity && pools.get(**e).is_some()).copied()
+                        };
+                        match target_entity {
+                            None => {
+                                self.ecs.fetch_mut::<gamelog::GameLog>().entries.push("There's nothing there to shoot at.".to_string());
+                                newrunstate = RunState::AwaitingInput;
+                            }
+                            Some(_) if loses_turn(&self.ecs) => newrunstate = RunState::PlayerTurn,
+                            Some(target_entity) => {
+                                let mut intent = self.ecs.write_storage::<WantsToShoot>();
+                                intent.insert(player_entity, WantsToShoot{ target: target_entity }).expect("Unable to insert intent!");
+                                newrunstate = RunState::PlayerTurn;
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::ShowSpells => {
+                let result = gui::show_spells(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let spell_name = result.1.unwrap();
+                        let spell = raws::spawn_named_spell(&raws::RAWS.lock().unwrap(), &mut self.ecs, &spell_name);
+                        match spell {
+                            None => newrunstate = RunState::AwaitingInput,
+                            Some(spell) => {
+                                let range = self.ecs.read_storage::<Ranged>().get(spell).map(|r| r.range);
+                                if let Some(range) = range {
+                                    newrunstate = RunState::ShowCasting{ range, spell };
+                                } else if loses_turn(&self.ecs) {
+                                    self.ecs.delete_entity(spell).expect("Unable to delete spell");
+                                    newrunstate = RunState::PlayerTurn;
+                                } else {
+                                    let mut intent = self.ecs.write_storage::<WantsToCastSpell>();
+                                    intent.insert(*self.ecs.fetch::<Entity>(), WantsToCastSpell{ spell, target: None }).expect("Unable to insert intent!");
+                                    newrunstate = RunState::PlayerTurn;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::ShowCasting{range, spell} => {
+                let result = gui::ranged_target(self, ctx, range);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => {
+                        self.ecs.delete_entity(spell).expect("Unable to delete spell");
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected if loses_turn(&self.ecs) => {
+                        self.ecs.delete_entity(spell).expect("Unable to delete spell");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                    gui::ItemMenuResult::Selected => {
+                        let mut intent = self.ecs.write_storage::<WantsToCastSpell>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToCastSpell{ spell, target: result.1 }).expect("Unable to insert intent!");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
             RunState::SaveGame => {
                 saveload_system::save_game(&mut self.ecs);
                 newrunstate = RunState::MainMenu{ menu_selection: gui::MainMenuSelection::LoadGame };
@@ -273,6 +443,7 @@ impl GameState for State {
                 match result.0 {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected if loses_turn(&self.ecs) => newrunstate = RunState::PlayerTurn,
                     gui::ItemMenuResult::Selected => {
                         let item_entity = result.1.unwrap();
                         let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
@@ -372,6 +543,7 @@ impl State {
             current_depth = worldmap_resource.depth;
         }
         self.generate_world_map(current_depth + 1);
+        experience::reached_depth(&mut self.ecs, current_depth + 1);
 
         // Notify the player and regenerate some health.
         let _player_entity = self.ecs.fetch::<Entity>();
@@ -405,7 +577,8 @@ impl State {
         self.mapgen_timer = 0.0;
         self.mapgen_history.clear();
         let mut rng = self.ecs.write_resource::<rltk::RandomNumberGenerator>();
-        let mut builder = map_builders::level_builder(new_depth, &mut rng, 80, 44);
+        let (width, height) = map_builders::level_size(new_depth, &mut rng);
+        let mut builder = map_builders::level_builder(new_depth, &mut rng, width, height);
         builder.build_map(&mut rng);
         std::mem::drop(rng);
         self.mapgen_history = builder.build_data.history.clone();
@@ -462,6 +635,7 @@ fn main() -> rltk::BError {
     gs.ecs.register::<Renderable>();
     gs.ecs.register::<Player>();
     gs.ecs.register::<SufferDamage>();
+    gs.ecs.register::<Resistances>();
     // AIs
     gs.ecs.register::<Monster>();
     gs.ecs.register::<Bystander>();
@@ -472,13 +646,15 @@ fn main() -> rltk::BError {
     gs.ecs.register::<Name>();
     gs.ecs.register::<Viewshed>();
     gs.ecs.register::<BlocksTile>();
-    gs.ecs.register::<Stunned>();
+    gs.ecs.register::<StatusEffects>();
+    gs.ecs.register::<InflictsStatus>();
     // Intents
     gs.ecs.register::<WantsToMelee>();
     gs.ecs.register::<WantsToPickupItem>();
     gs.ecs.register::<WantsToDropItem>();
     gs.ecs.register::<WantsToUseItem>();
     gs.ecs.register::<WantsToRemoveItem>();
+    gs.ecs.register::<WantsToSearch>();
     // Item properties
     gs.ecs.register::<Item>();
     gs.ecs.register::<ProvidesHealing>();
@@ -493,6 +669,17 @@ fn main() -> rltk::BError {
     gs.ecs.register::<Equipped>();
     gs.ecs.register::<MeleeWeapon>();
     gs.ecs.register::<Wearable>();
+    gs.ecs.register::<Shield>();
+    gs.ecs.register::<Encumbrance>();
+    gs.ecs.register::<Morale>();
+    gs.ecs.register::<Awareness>();
+    gs.ecs.register::<Faction>();
+    gs.ecs.register::<WantsToReact>();
+    gs.ecs.register::<Grudges>();
+    gs.ecs.register::<MyTurn>();
+    gs.ecs.register::<WantsToApproach>();
+    gs.ecs.register::<WantsToFlee>();
+    gs.ecs.register::<Chasing>();
     gs.ecs.register::<ParticleLifetime>();
     gs.ecs.register::<HungerClock>();
     gs.ecs.register::<ProvidesFood>();
@@ -504,6 +691,32 @@ fn main() -> rltk::BError {
     gs.ecs.register::<RemembersPlayer>();
     gs.ecs.register::<BlocksVisibility>();
     gs.ecs.register::<Door>();
+    gs.ecs.register::<SecretDoor>();
+    gs.ecs.register::<Locked>();
+    gs.ecs.register::<Key>();
+    gs.ecs.register::<LightSource>();
+    gs.ecs.register::<Swimming>();
+    gs.ecs.register::<StartsFire>();
+    gs.ecs.register::<CreatesCloud>();
+    gs.ecs.register::<Teleports>();
+    gs.ecs.register::<RaisesAlarm>();
+    gs.ecs.register::<Pitfall>();
+    gs.ecs.register::<Entangles>();
+    gs.ecs.register::<Disarmable>();
+    gs.ecs.register::<WantsToDisarm>();
+    gs.ecs.register::<RangedWeapon>();
+    gs.ecs.register::<Ammunition>();
+    gs.ecs.register::<WantsToShoot>();
+    gs.ecs.register::<WantsToCastSpell>();
+    gs.ecs.register::<Spell>();
+    gs.ecs.register::<KnownSpells>();
+    gs.ecs.register::<TeachesSpell>();
+    gs.ecs.register::<SpecialAbilities>();
+    gs.ecs.register::<WantsToUseAbility>();
+    gs.ecs.register::<Charges>();
+    gs.ecs.register::<Summons>();
+    gs.ecs.register::<WantsToSummon>();
+    gs.ecs.register::<Burning>();
     gs.ecs.register::<Quips>();
     gs.ecs.register::<Attributes>();
     gs.ecs.register::<Skills>();
@@ -533,6 +746,7 @@ fn main() -> rltk::BError {
     gs.ecs.insert(RunState::MapGeneration{} );
     gs.ecs.insert(gamelog::GameLog{ entries: vec!["Welcome to Rustlike!".to_string()]});
     gs.ecs.insert(particle_system::ParticleBuilder::new());
+    gs.ecs.insert(stealth_system::NoiseBuilder::new());
     gs.ecs.insert(rex_assets::RexAssets::new());
 
     gs.generate_world_map(1);
@@ -0,0 +1,50 @@
+use serde::{Serialize, Deserialize};
+use rltk::RGB;
+use crate::DamageType;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A patch of gas, mist or smoke hanging over a tile. What it looks like and does is looked up by
+/// name in the cloud registry.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Cloud {
+    pub name: String,
+    pub density: i32,
+}
+
+/// Everything we know about a type of cloud.
+#[derive(Clone)]
+pub struct CloudInfo {
+    pub color: RGB,
+    /// How much the cloud tints whatever is behind it, from 0 (not at all) to 1 (completely)
+    pub opacity: f32,
+    /// How thick a freshly released cloud is
+    pub density: i32,
+    /// How much the cloud thins out each turn; clouds which don't decay linger forever
+    pub decay: i32,
+    /// Does the cloud drift into neighbouring tiles?
+    pub spreads: bool,
+    pub blocks_visibility: bool,
+    /// Dice rolled for the damage dealt to anyone standing in the cloud each turn
+    pub damage: Option<String>,
+    pub damage_type: DamageType,
+    /// Hit points restored to anyone standing in the cloud each turn
+    pub healing: i32,
+    /// Turns of confusion inflicted on anyone standing in the cloud
+    pub confusion: i32,
+}
+
+/// Set once when the raws load, like the tile registry, so looking a cloud up never has to wait on
+/// a lock.
+static CLOUDS: OnceLock<HashMap<String, CloudInfo>> = OnceLock::new();
+
+/// Installs the cloud registry built from the raws. It can only be installed once.
+pub fn register_clouds(clouds: HashMap<String, CloudInfo>) {
+    if CLOUDS.set(clouds).is_err() {
+        rltk::console::log("WARNING - clouds have already been registered, ignoring the new set");
+    }
+}
+
+pub fn cloud_info(name: &str) -> Option<&'static CloudInfo> {
+    CLOUDS.get().and_then(|clouds| clouds.get(name))
+}
@@ -1,14 +1,26 @@
-use rltk::{ Point, Algorithm2D, BaseMap};
-use std::collections::HashSet;
+use rltk::{ Point, Algorithm2D, BaseMap, RGB};
+use std::collections::{HashMap, HashSet};
 use specs::prelude::*;
 use serde::{ Serialize, Deserialize, };
 
 mod tiletype;
-pub use tiletype::{TileType, tile_walkable, tile_opaque, tile_cost};
+pub use tiletype::{
+    TileType, TileInfo, TileLook, TileRegistry, register_tiles, tile_walkable, tile_opaque,
+    tile_cost, tile_has_flag, tile_hazardous, tile_look,
+};
 mod themes;
 pub use themes::*;
+mod clouds;
+pub use clouds::{Cloud, CloudInfo, register_clouds, cloud_info};
 
 
+/// How much light a level gets before any light sources are added. The surface is in daylight,
+/// and below that, the deeper you go the darker it gets.
+fn ambient_light(depth: i32) -> f32 {
+    if depth <= 2 { return 1.0; }
+    f32::max(0.0, 1.0 - (depth - 2) as f32 * 0.2)
+}
+
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Map {
     pub tiles: Vec<TileType>,
@@ -20,11 +32,35 @@ pub struct Map {
     pub depth: i32,
     pub bloodstains: HashSet<usize>,
     pub view_blocked: HashSet<usize>,
+    /// Tiles which are on fire, and how many more turns they'll burn for
+    pub fire: HashMap<usize, i32>,
+    /// Gas, mist and smoke hanging over the map
+    pub clouds: HashMap<usize, Cloud>,
     pub name: String,
+    /// The visual theme used to draw the map's tiles
+    pub theme: String,
+    pub ambient_light: f32,
+    pub light: Vec<RGB>,
 
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
     pub tile_content: Vec<Vec<Entity>>,
+
+    /// Pathfinding normally steers around hazards; this lets a panicking creature run straight
+    /// through them.
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    pub path_through_hazards: bool,
+
+    /// Closed doors which can be opened by anyone with hands, rebuilt along with `tile_content`.
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    pub closed_doors: HashSet<usize>,
+
+    /// Lets pathfinding treat closed doors as open, for creatures who can work the handle.
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    pub path_through_doors: bool,
 }
 
 impl Map {
@@ -35,7 +71,13 @@ impl Map {
     fn is_exit_valid(&self, x:i32, y:i32) -> bool {
         if x < 1 || x > self.width-1 || y < 1 || y > self.height-1 { return false; }
         let idx = self.xy_idx(x, y);
-        !self.blocked[idx]
+        let passable = !self.blocked[idx] || (self.path_through_doors && self.closed_doors.contains(&idx));
+        passable && (self.path_through_hazards || !self.is_hazardous(idx))
+    }
+
+    /// Is this tile dangerous to stand on, either because of what it is or because it's on fire?
+    pub fn is_hazardous(&self, idx: usize) -> bool {
+        tile_hazardous(self.tiles[idx]) || self.fire.contains_key(&idx)
     }
 
     pub fn populate_blocked(&mut self) {
@@ -44,6 +86,21 @@ impl Map {
         }
     }
 
+    /// Works out where something which has just moved by (dx, dy) onto (x, y) comes to rest. On
+    /// slippery ground it keeps sliding the same way until it leaves the ice or hits something.
+    pub fn slide(&self, x: i32, y: i32, dx: i32, dy: i32) -> (i32, i32) {
+        if dx == 0 && dy == 0 { return (x, y); }
+        let (mut x, mut y) = (x, y);
+        while tile_has_flag(self.tiles[self.xy_idx(x, y)], "slippery") {
+            let (next_x, next_y) = (x + dx, y + dy);
+            if next_x < 1 || next_x > self.width-2 || next_y < 1 || next_y > self.height-2 { break; }
+            if self.blocked[self.xy_idx(next_x, next_y)] { break; }
+            x = next_x;
+            y = next_y;
+        }
+        (x, y)
+    }
+
     pub fn clear_content_index(&mut self) {
         for content in self.tile_content.iter_mut() {
             content.clear();
@@ -63,7 +120,15 @@ impl Map {
             depth: new_depth,
             bloodstains: HashSet::new(),
             view_blocked: HashSet::new(),
+            fire: HashMap::new(),
+            clouds: HashMap::new(),
             name: name.to_string(),
+            theme: "Dungeon".to_string(),
+            ambient_light: ambient_light(new_depth),
+            light: vec![RGB::from_f32(1.0, 1.0, 1.0); map_tile_count],
+            path_through_hazards: false,
+            closed_doors: HashSet::new(),
+            path_through_doors: false,
         }
     }
 }
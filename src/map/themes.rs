@@ -1,65 +1,55 @@
-use super::{Map, TileType};
+use super::{Map, tile_look, tile_opaque, cloud_info};
 use rltk::RGB;
 
 pub fn tile_glyph(idx: usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let (glyph, mut fg, mut bg) = match map.depth{
-        2 => get_forest_glyph(idx, map),
-        _ => get_tile_glyph_default(idx, map)
-    };
+    let look = tile_look(map.tiles[idx], &map.theme);
+    let mut glyph = look.glyph;
+    let mut fg = look.fg;
+    let mut bg = look.bg;
+    if look.connect_walls {
+        glyph = wall_glyph(map, idx as i32 % map.width, idx as i32 / map.width);
+    }
 
     if map.bloodstains.contains(&idx) { bg = RGB::from_f32(0.75, 0.0, 0.0); }
+    if map.visible_tiles[idx] && map.fire.contains_key(&idx) {
+        // Fire is only drawn while you can see it
+        glyph = rltk::to_cp437('^');
+        fg = RGB::from_f32(1.0, 0.6, 0.0);
+        bg = RGB::from_f32(0.6, 0.1, 0.0);
+    }
     if !map.visible_tiles[idx] {
         fg = fg.to_greyscale();
         bg = RGB::from_f32(0., 0., 0.); // Don't show bloodstains outside visual range
+    } else {
+        fg = shade(fg, map.light[idx]);
+        bg = shade(bg, map.light[idx]);
+        fg = cloud_tint(map, idx, fg, 0.5);
+        bg = cloud_tint(map, idx, bg, 1.0);
     }
 
     (glyph, fg, bg)
 }
 
-fn get_tile_glyph_default(idx: usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Floor => { glyph = rltk::to_cp437('.'); fg = RGB::from_f32(0.0, 0.5, 0.5); }
-        TileType::WoodFloor => { glyph = rltk::to_cp437('.'); fg = RGB::named(rltk::CHOCOLATE); }
-        TileType::Wall => {
-            let x = idx as i32 % map.width;
-            let y = idx as i32 / map.width;
-            glyph = wall_glyph(&*map, x, y);
-            fg = RGB::from_f32(0., 0.7, 0.);
+/// Blends a colour with any cloud hanging over the tile. Thin clouds are fainter than thick ones,
+/// and `strength` scales how much the cloud shows through (glyphs are tinted less than backgrounds).
+pub fn cloud_tint(map: &Map, idx: usize, color: RGB, strength: f32) -> RGB {
+    let cloud = match map.clouds.get(&idx) {
+        None => return color,
+        Some(cloud) => cloud,
+    };
+    match cloud_info(&cloud.name) {
+        None => color,
+        Some(info) => {
+            let alpha = info.opacity * f32::min(1.0, cloud.density as f32 / 3.0) * strength;
+            color.lerp(info.color, alpha)
         }
-        TileType::DownStairs => { glyph = rltk::to_cp437('>'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        TileType::Bridge => { glyph = rltk::to_cp437('.'); fg = RGB::named(rltk::CHOCOLATE); }
-        TileType::Road => { glyph = rltk::to_cp437('≡'); fg = RGB::named(rltk::GREY); }
-        TileType::Grass => { glyph = rltk::to_cp437('"'); fg = RGB::named(rltk::GREEN); }
-        TileType::ShallowWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::CYAN); }
-        TileType::DeepWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::NAVY_BLUE); }
-        TileType::Gravel => { glyph = rltk::to_cp437(';'); fg = RGB::named(rltk::GREY); }
     }
-
-    (glyph, fg, bg)
 }
 
-fn get_forest_glyph(idx:usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Wall => { glyph = rltk::to_cp437('♣'); fg = RGB::from_f32(0.0, 0.6, 0.0); }
-        TileType::Bridge => { glyph = rltk::to_cp437('.'); fg = RGB::named(rltk::CHOCOLATE); }
-        TileType::Road => { glyph = rltk::to_cp437('≡'); fg = RGB::named(rltk::YELLOW); }
-        TileType::Grass => { glyph = rltk::to_cp437('"'); fg = RGB::named(rltk::GREEN); }
-        TileType::ShallowWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::CYAN); }
-        TileType::DeepWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::BLUE); }
-        TileType::Gravel => { glyph = rltk::to_cp437(';'); fg = RGB::from_f32(0.5, 0.5, 0.5); }
-        TileType::DownStairs => { glyph = rltk::to_cp437('>'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        _ => { glyph = rltk::to_cp437('"'); fg = RGB::from_f32(0.0, 0.6, 0.0); }
-    }
-
-    (glyph, fg, bg)
+/// Tints a colour by the light falling on its tile, without ever quite going black.
+fn shade(color: RGB, light: RGB) -> RGB {
+    const MIN_SHADE: f32 = 0.3;
+    color * RGB::from_f32(f32::max(MIN_SHADE, light.r), f32::max(MIN_SHADE, light.g), f32::max(MIN_SHADE, light.b))
 }
 
 fn wall_glyph(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
@@ -94,5 +84,5 @@ fn wall_glyph(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
 fn is_revealed_and_wall(map: &Map, x: i32, y: i32) -> bool {
     if x < 0 || x > map.width - 1 || y < 0 || y > map.height - 1 as i32 { return false; }
     let idx = map.xy_idx(x, y);
-    map.tiles[idx] == TileType::Wall && map.revealed_tiles[idx]
+    tile_opaque(map.tiles[idx]) && map.revealed_tiles[idx]
 }
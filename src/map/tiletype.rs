@@ -1,40 +1,185 @@
-use serde::{Serialize, Deserialize};
-
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
-pub enum TileType {
-    Wall,
-    Floor,
-    DownStairs,
-    Road,
-    Grass,
-    ShallowWater,
-    DeepWater,
-    WoodFloor,
-    Bridge,
-    Gravel
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use rltk::RGB;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// A type of map tile. Tiles are defined in the raws: the ones the game's code refers to by name
+/// have constants here, and any others the raws add are given an id of their own when they load.
+/// Since those ids depend on the order of the raws, tiles are saved by name.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct TileType(u16);
+
+#[allow(non_upper_case_globals)]
+impl TileType {
+    pub const Wall: TileType = TileType(0);
+    pub const Floor: TileType = TileType(1);
+    pub const DownStairs: TileType = TileType(2);
+    pub const Road: TileType = TileType(3);
+    pub const Grass: TileType = TileType(4);
+    pub const ShallowWater: TileType = TileType(5);
+    pub const DeepWater: TileType = TileType(6);
+    pub const WoodFloor: TileType = TileType(7);
+    pub const Bridge: TileType = TileType(8);
+    pub const Gravel: TileType = TileType(9);
+    pub const Lava: TileType = TileType(10);
+    pub const Chasm: TileType = TileType(11);
+    pub const Ice: TileType = TileType(12);
+    pub const Scorched: TileType = TileType(13);
+    pub const SwimmableWater: TileType = TileType(14);
+
+    /// Looks up a tile by the name the raws give it.
+    pub fn named(name: &str) -> Option<TileType> {
+        TILES.get().and_then(|registry| registry.id_of(name))
+    }
 }
 
-pub fn tile_walkable(tt: TileType) -> bool {
-    match tt {
-        TileType::Floor | TileType::DownStairs | TileType::Road | TileType::Grass |
-        TileType::ShallowWater | TileType::WoodFloor | TileType::Bridge | TileType::Gravel
-            => true,
-        _ => false
+impl Serialize for TileType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match TILES.get().and_then(|registry| registry.name_of(*self)) {
+            Some(name) => serializer.serialize_str(name),
+            None => Err(serde::ser::Error::custom(format!("unknown tile id {}", self.0))),
+        }
     }
 }
 
-pub fn tile_opaque(tt: TileType) -> bool {
-    match tt {
-        TileType::Wall => true,
-        _ => false
+impl<'de> Deserialize<'de> for TileType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        TileType::named(&name).ok_or_else(|| serde::de::Error::custom(format!("unknown tile [{}]", name)))
+    }
+}
+
+/// The names of the tiles which have constants, in id order.
+const BUILTIN_TILES: [&str; 15] = [
+    "Wall", "Floor", "DownStairs", "Road", "Grass", "ShallowWater", "DeepWater", "WoodFloor",
+    "Bridge", "Gravel", "Lava", "Chasm", "Ice", "Scorched", "SwimmableWater",
+];
+
+/// What a tile looks like.
+#[derive(Clone, Copy)]
+pub struct TileLook {
+    pub glyph: rltk::FontCharType,
+    pub fg: RGB,
+    pub bg: RGB,
+    /// Draw this tile with box-drawing characters which join up with neighbouring walls
+    pub connect_walls: bool,
+}
+
+/// Everything we know about a type of tile.
+#[derive(Clone)]
+pub struct TileInfo {
+    pub walkable: bool,
+    pub opaque: bool,
+    pub cost: f32,
+    pub look: TileLook,
+    pub flags: HashSet<String>,
+    /// Whether the tile has the "hazard" flag, kept separately because pathfinding asks so often
+    pub hazardous: bool,
+}
+
+/// All the tile types, and the visual themes which can change how they look. Loaded from the raws.
+pub struct TileRegistry {
+    names: HashMap<String, TileType>,
+    ids: Vec<String>,
+    tiles: Vec<Option<TileInfo>>,
+    themes: HashMap<String, HashMap<TileType, TileLook>>,
+}
+
+impl Default for TileRegistry {
+    fn default() -> Self {
+        let names = BUILTIN_TILES.iter().enumerate()
+            .map(|(id, name)| (name.to_string(), TileType(id as u16)))
+            .collect();
+        let ids = BUILTIN_TILES.iter().map(|name| name.to_string()).collect();
+        TileRegistry{ names, ids, tiles: vec![None; BUILTIN_TILES.len()], themes: HashMap::new() }
+    }
+}
+
+impl TileRegistry {
+    /// Returns the id of the named tile, handing out a new one if it hasn't been seen before.
+    pub fn tile_id(&mut self, name: &str) -> TileType {
+        if let Some(id) = self.names.get(name) {
+            return *id;
+        }
+        let id = TileType(self.tiles.len() as u16);
+        self.names.insert(name.to_string(), id);
+        self.ids.push(name.to_string());
+        self.tiles.push(None);
+        id
+    }
+
+    pub fn id_of(&self, name: &str) -> Option<TileType> {
+        self.names.get(name).copied()
+    }
+
+    pub fn name_of(&self, tt: TileType) -> Option<&str> {
+        self.ids.get(tt.0 as usize).map(|name| name.as_str())
+    }
+
+    pub fn get(&self, tt: TileType) -> Option<&TileInfo> {
+        self.tiles.get(tt.0 as usize).and_then(|t| t.as_ref())
+    }
+
+    pub fn insert(&mut self, tt: TileType, info: TileInfo) {
+        self.tiles[tt.0 as usize] = Some(info);
+    }
+
+    pub fn add_theme(&mut self, name: &str, looks: HashMap<TileType, TileLook>) {
+        self.themes.insert(name.to_string(), looks);
+    }
+}
+
+/// Set once when the raws load, and only read after that, so looking a tile up never has to wait
+/// on a lock.
+static TILES: OnceLock<TileRegistry> = OnceLock::new();
+
+/// Installs the tile registry built from the raws. It can only be installed once.
+pub fn register_tiles(registry: TileRegistry) {
+    if TILES.set(registry).is_err() {
+        rltk::console::log("WARNING - tiles have already been registered, ignoring the new set");
     }
 }
 
+fn tile_info(tt: TileType) -> Option<&'static TileInfo> {
+    TILES.get().and_then(|registry| registry.get(tt))
+}
+
+pub fn tile_walkable(tt: TileType) -> bool {
+    tile_info(tt).is_some_and(|t| t.walkable)
+}
+
+pub fn tile_opaque(tt: TileType) -> bool {
+    tile_info(tt).is_none_or(|t| t.opaque)
+}
+
 pub fn tile_cost(tt: TileType) -> f32 {
-    match tt {
-        TileType::Road => 0.8,
-        TileType::Grass => 1.1,
-        TileType::ShallowWater => 1.2,
-        _ => 1.0
+    tile_info(tt).map_or(1.0, |t| t.cost)
+}
+
+/// Hazardous tiles can be walked on, but something nasty happens to whatever does. Pathfinding
+/// steers around them.
+pub fn tile_hazardous(tt: TileType) -> bool {
+    tile_info(tt).is_some_and(|t| t.hazardous)
+}
+
+pub fn tile_has_flag(tt: TileType, flag: &str) -> bool {
+    tile_info(tt).is_some_and(|t| t.flags.contains(flag))
+}
+
+/// Returns what a tile looks like in the given theme. Tiles the theme doesn't mention look the same
+/// as they do everywhere else.
+pub fn tile_look(tt: TileType, theme: &str) -> TileLook {
+    let themed = TILES.get().and_then(|registry| registry.themes.get(theme)).and_then(|t| t.get(&tt));
+    if let Some(look) = themed {
+        return *look;
+    }
+    match tile_info(tt) {
+        Some(info) => info.look,
+        None => TileLook{
+            glyph: rltk::to_cp437('?'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            connect_walls: false,
+        }
     }
 }
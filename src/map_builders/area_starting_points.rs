@@ -1,4 +1,4 @@
-use super::{MetaMapBuilder, BuilderMap, Position, tile_walkable};
+use super::{MetaMapBuilder, BuilderMap, Position, tile_walkable, tile_hazardous};
 use rltk::RandomNumberGenerator;
 
 #[allow(dead_code)]
@@ -44,7 +44,7 @@ impl AreaStartingPosition {
 
         let mut available_floors: Vec<(usize, f32)> = Vec::new();
         for (idx, tiletype) in build_data.map.tiles.iter().enumerate() {
-            if tile_walkable(*tiletype) {
+            if tile_walkable(*tiletype) && !tile_hazardous(*tiletype) {
                 available_floors.push(
                     (
                         idx,
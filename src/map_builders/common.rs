@@ -1,4 +1,4 @@
-use super::{Map, TileType };
+use super::{Map, TileType, BuilderMap, tile_walkable, tile_hazardous};
 use std::cmp::{max, min};
 
 pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) -> Vec<usize> {
@@ -55,50 +55,54 @@ pub fn draw_corridor(map: &mut Map, x1: i32, y1: i32, x2: i32, y2: i32) -> Vec<u
 #[allow(dead_code)]
 pub enum Symmetry { None, Horizontal, Vertical, Both }
 
-pub fn paint(map: &mut Map, mode: Symmetry, brush_size: i32, x: i32, y: i32) {
+/// Digs out floor with a brush, mirrored according to `mode`. Returns how many walls it dug out,
+/// so builders can keep count of their floor space without scanning the whole map each time.
+pub fn paint(map: &mut Map, mode: Symmetry, brush_size: i32, x: i32, y: i32) -> usize {
     match mode {
         Symmetry::None => apply_paint(map, brush_size, x, y),
         Symmetry::Horizontal => {
             let center_x = map.width / 2;
             if x == center_x {
-                apply_paint(map, brush_size, x, y);
+                apply_paint(map, brush_size, x, y)
             } else {
                 let dist_x = i32::abs(center_x - x);
-                apply_paint(map, brush_size, center_x + dist_x, y);
-                apply_paint(map, brush_size, center_x - dist_x, y);
+                apply_paint(map, brush_size, center_x + dist_x, y)
+                    + apply_paint(map, brush_size, center_x - dist_x, y)
             }
         }
         Symmetry::Vertical => {
             let center_y = map.height / 2;
             if y == center_y {
-                apply_paint(map, brush_size, x, y);
+                apply_paint(map, brush_size, x, y)
             } else {
                 let dist_y = i32::abs(center_y - y);
-                apply_paint(map, brush_size, x, center_y + dist_y);
-                apply_paint(map, brush_size, x, center_y - dist_y);
+                apply_paint(map, brush_size, x, center_y + dist_y)
+                    + apply_paint(map, brush_size, x, center_y - dist_y)
             }
         }
         Symmetry::Both => {
             let center_x = map.width / 2;
             let center_y = map.height / 2;
             if x == center_x && y == center_y {
-                apply_paint(map, brush_size, x, y);
+                apply_paint(map, brush_size, x, y)
             } else {
                 let dist_x = i32::abs(center_x - x);
                 let dist_y = i32::abs(center_y - y);
-                apply_paint(map, brush_size, center_x + dist_x, center_y + dist_y);
-                apply_paint(map, brush_size, center_x - dist_x, center_y + dist_y);
-                apply_paint(map, brush_size, center_x + dist_x, center_y - dist_y);
-                apply_paint(map, brush_size, center_x - dist_x, center_y - dist_y);
+                apply_paint(map, brush_size, center_x + dist_x, center_y + dist_y)
+                    + apply_paint(map, brush_size, center_x - dist_x, center_y + dist_y)
+                    + apply_paint(map, brush_size, center_x + dist_x, center_y - dist_y)
+                    + apply_paint(map, brush_size, center_x - dist_x, center_y - dist_y)
             }
         }
     }
 }
 
-fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) {
+fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) -> usize {
+    let mut dug = 0;
     match brush_size {
         1 => {
             let digger_idx = map.xy_idx(x, y);
+            if map.tiles[digger_idx] == TileType::Wall { dug += 1; }
             map.tiles[digger_idx] = TileType::Floor;
         }
         _ => {
@@ -107,10 +111,59 @@ fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) {
                 for brush_x in x-half_brush_size .. x+half_brush_size {
                     if brush_x > 1 && brush_x < map.width - 1 && brush_y > 1 && brush_y < map.height - 1 {
                         let idx = map.xy_idx(brush_x, brush_y);
+                        if map.tiles[idx] == TileType::Wall { dug += 1; }
                         map.tiles[idx] = TileType::Floor;
                     }
                 }
             }
         }
     }
+    dug
+}
+
+/// Makes sure the exit can still be reached from the start after `tile` has been spread across the
+/// map, turning the tiles of `tile` on the way into `crossing` (bridges over water or chasms, a
+/// crust of cooled rock over lava) if it's in the way.
+pub fn lay_crossing(build_data: &mut BuilderMap, tile: TileType, crossing: TileType) {
+    let start_idx = match &build_data.starting_position {
+        None => return,
+        Some(start) => build_data.map.xy_idx(start.x, start.y),
+    };
+    let exit_idx = match build_data.map.tiles.iter().position(|t| *t == TileType::DownStairs) {
+        None => return,
+        Some(idx) => idx,
+    };
+
+    build_data.map.populate_blocked();
+    if rltk::a_star_search(start_idx, exit_idx, &build_data.map).success { return; }
+
+    // Path as if it were already crossable, then make that path so
+    let mut crossable_map = build_data.map.clone();
+    for t in crossable_map.tiles.iter_mut() {
+        if *t == tile { *t = crossing; }
+    }
+    crossable_map.populate_blocked();
+    let path = rltk::a_star_search(start_idx, exit_idx, &crossable_map);
+    for idx in path.steps.iter() {
+        if build_data.map.tiles[*idx] == tile {
+            build_data.map.tiles[*idx] = crossing;
+        }
+    }
+}
+
+/// Removes anything which was due to spawn somewhere it can't stand, or on a hazard, or which has
+/// been cut off from the starting position.
+pub fn cull_cut_off_spawns(build_data: &mut BuilderMap) {
+    let start_idx = match &build_data.starting_position {
+        None => return,
+        Some(start) => build_data.map.xy_idx(start.x, start.y),
+    };
+    build_data.map.populate_blocked();
+    let map_starts: Vec<usize> = vec![start_idx];
+    let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &map_starts, &build_data.map, 1000.0);
+    let map = &build_data.map;
+    build_data.spawn_list.retain(|(idx, _name)| {
+        let tile = map.tiles[*idx];
+        tile_walkable(tile) && !tile_hazardous(tile) && dijkstra_map.map[*idx] != f32::MAX
+    });
 }
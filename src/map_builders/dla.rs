@@ -128,7 +128,7 @@ impl DLABuilder {
                         }
                         digger_idx = build_data.map.xy_idx(digger_x, digger_y);
                     }
-                    paint(&mut build_data.map, self.symmetry, self.brush_size, prev_x, prev_y);
+                    floor_tile_count += paint(&mut build_data.map, self.symmetry, self.brush_size, prev_x, prev_y);
                 }
                 DLAAlgorithm::WalkOutwards => {
                     let mut digger_x = starting_position.x;
@@ -144,7 +144,7 @@ impl DLABuilder {
                         }
                         digger_idx = build_data.map.xy_idx(digger_x, digger_y);
                     }
-                    paint(&mut build_data.map, self.symmetry, self.brush_size, digger_x, digger_y);
+                    floor_tile_count += paint(&mut build_data.map, self.symmetry, self.brush_size, digger_x, digger_y);
                 }
                 DLAAlgorithm::CentralAttractor => {
                     let mut digger_x = rng.roll_dice(1, build_data.map.width - 3) + 1;
@@ -167,7 +167,7 @@ impl DLABuilder {
                         path.remove(0);
                         digger_idx = build_data.map.xy_idx(digger_x, digger_y);
                     }
-                    paint(&mut build_data.map, self.symmetry, self.brush_size, prev_x, prev_y);
+                    floor_tile_count += paint(&mut build_data.map, self.symmetry, self.brush_size, prev_x, prev_y);
                 }
             }
 
@@ -175,7 +175,6 @@ impl DLABuilder {
                 build_data.take_snapshot();
             }
             i += 1;
-            floor_tile_count = build_data.map.tiles.iter().filter(|a| **a == TileType::Floor).count();
         }
     }
 }
@@ -130,14 +130,16 @@ impl DrunkardsWalkBuilder {
                 }
             }
             let mut drunk_life = self.settings.drunken_lifetime;
+            let mut trail: Vec<usize> = Vec::new();
 
             while drunk_life > 0 {
                 let drunk_idx = build_data.map.xy_idx(drunk_x, drunk_y);
                 if build_data.map.tiles[drunk_idx] == TileType::Wall {
                     did_something = true;
                 }
-                paint(&mut build_data.map, self.settings.symmetry, self.settings.brush_size, drunk_x, drunk_y);
+                floor_tile_count += paint(&mut build_data.map, self.settings.symmetry, self.settings.brush_size, drunk_x, drunk_y);
                 build_data.map.tiles[drunk_idx] = TileType::DownStairs;
+                trail.push(drunk_idx);
 
                 let stagger_direction = rng.roll_dice(1, 4);
                 match stagger_direction {
@@ -155,12 +157,9 @@ impl DrunkardsWalkBuilder {
             }
 
             digger_count += 1;
-            for t in build_data.map.tiles.iter_mut() {
-                if *t == TileType::DownStairs {
-                    *t = TileType::Floor;
-                }
+            for idx in trail.iter() {
+                build_data.map.tiles[*idx] = TileType::Floor;
             }
-            floor_tile_count = build_data.map.tiles.iter().filter(|a| **a == TileType::Floor).count();
         }
         rltk::console::log(format!("{} dwarves gave up their sobriety, of whom {} actually found a wall.", digger_count, active_digger_count));
     }
@@ -0,0 +1,48 @@
+use super::{MetaMapBuilder, BuilderMap, tile_has_flag, tile_walkable};
+use crate::cloud_system::add_cloud;
+use rltk::RandomNumberGenerator;
+
+/// Lets banks of fog settle over water and the ground around it, turning lakes into murky swamps
+/// you can't see far across. The fog never lifts.
+pub struct FogBanks {
+    /// How far from the water the fog creeps
+    reach: i32,
+}
+
+impl MetaMapBuilder for FogBanks {
+    fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl FogBanks {
+    #[allow(dead_code)]
+    pub fn new() -> Box<FogBanks> {
+        Box::new(FogBanks{ reach: 2 })
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let width = build_data.map.width;
+        let height = build_data.map.height;
+        let water: Vec<usize> = build_data.map.tiles.iter().enumerate()
+            .filter(|(_idx, tile)| tile_has_flag(**tile, "water"))
+            .map(|(idx, _tile)| idx)
+            .collect();
+
+        for idx in water.iter() {
+            let (x, y) = (*idx as i32 % width, *idx as i32 / width);
+            for fy in y - self.reach ..= y + self.reach {
+                for fx in x - self.reach ..= x + self.reach {
+                    if fx < 1 || fx > width-2 || fy < 1 || fy > height-2 { continue; }
+                    let fog_idx = build_data.map.xy_idx(fx, fy);
+                    if !tile_walkable(build_data.map.tiles[fog_idx]) { continue; }
+                    // Patchy at the edges, thick over the water itself
+                    if *idx == fog_idx || rng.roll_dice(1, 3) == 1 {
+                        add_cloud(&mut build_data.map, fog_idx, "Fog");
+                    }
+                }
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
@@ -1,11 +1,13 @@
 use super::{
     BuilderChain, CellularAutomataBuilder, XStart, YStart, AreaStartingPosition,
     CullUnreachable, VoronoiSpawning, MetaMapBuilder, BuilderMap, TileType,
+    RiversAndLakes,
 };
 use crate::map;
 
 pub fn forest_builder(new_depth: i32, _rng: &mut rltk::RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
     let mut chain = BuilderChain::new(new_depth, width, height, "Into the Woods");
+    chain.with_theme("Forest");
     chain.start_with(CellularAutomataBuilder::new());
     chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
     chain.with(CullUnreachable::new());
@@ -14,6 +16,7 @@ pub fn forest_builder(new_depth: i32, _rng: &mut rltk::RandomNumberGenerator, wi
     // Set up an exit & spawn mobs
     chain.with(VoronoiSpawning::new());
     chain.with(YellowBrickRoad::new());
+    chain.with(RiversAndLakes::wetlands());
     chain
 }
 
@@ -34,7 +37,7 @@ impl YellowBrickRoad {
     fn find_exit(&self, build_data: &mut BuilderMap, seed_x: i32, seed_y: i32) -> (i32, i32) {
         let mut available_floors: Vec<(usize, f32)> = Vec::new();
         for (idx, tiletype) in build_data.map.tiles.iter().enumerate() {
-            if map::tile_walkable(*tiletype) {
+            if map::tile_walkable(*tiletype) && !map::tile_hazardous(*tiletype) {
                 available_floors.push(
                     (
                         idx,
@@ -0,0 +1,99 @@
+use super::{MetaMapBuilder, BuilderMap, TileType, tile_walkable, tile_hazardous, lay_crossing, cull_cut_off_spawns};
+use rltk::{RandomNumberGenerator, Point};
+use std::collections::HashSet;
+
+/// Scatters patches of hazardous terrain - lava, chasms or ice - across open ground.
+///
+/// Patches never cover the stairs or the starting tile. If they cut the exit off from the start,
+/// a safe way across is laid through them (a bridge over a chasm, a crust of cooled rock over
+/// lava), and anything which was due to spawn on a hazard or out of reach is dropped.
+pub struct Hazards {
+    tile: TileType,
+    crossing: TileType,
+    n_patches: i32,
+    max_radius: i32,
+}
+
+impl MetaMapBuilder for Hazards {
+    fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl Hazards {
+    #[allow(dead_code)]
+    pub fn new(tile: TileType, crossing: TileType, n_patches: i32, max_radius: i32) -> Box<Hazards> {
+        Box::new(Hazards{ tile, crossing, n_patches, max_radius })
+    }
+
+    /// A few pools of lava.
+    #[allow(dead_code)]
+    pub fn lava() -> Box<Hazards> {
+        Hazards::new(TileType::Lava, TileType::Gravel, 3, 4)
+    }
+
+    /// A couple of wide chasms, spanned by bridges where they're in the way.
+    #[allow(dead_code)]
+    pub fn chasms() -> Box<Hazards> {
+        Hazards::new(TileType::Chasm, TileType::Bridge, 2, 5)
+    }
+
+    /// Big sheets of ice.
+    #[allow(dead_code)]
+    pub fn ice() -> Box<Hazards> {
+        Hazards::new(TileType::Ice, TileType::Floor, 3, 6)
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let mut protected = HashSet::new();
+        if let Some(start) = &build_data.starting_position {
+            protected.insert(build_data.map.xy_idx(start.x, start.y));
+        }
+        for (idx, tile) in build_data.map.tiles.iter().enumerate() {
+            if *tile == TileType::DownStairs {
+                protected.insert(idx);
+            }
+        }
+
+        for _i in 0..self.n_patches {
+            self.patch(rng, build_data, &protected);
+        }
+
+        lay_crossing(build_data, self.tile, self.crossing);
+        cull_cut_off_spawns(build_data);
+        build_data.take_snapshot();
+    }
+
+    /// Can this tile be turned into the hazard?
+    fn can_cover(&self, build_data: &BuilderMap, protected: &HashSet<usize>, idx: usize) -> bool {
+        let tile = build_data.map.tiles[idx];
+        tile_walkable(tile) && !tile_hazardous(tile) && !protected.contains(&idx)
+            && tile != TileType::Road && tile != TileType::Bridge
+    }
+
+    /// Covers a ragged blob of open ground with the hazard.
+    fn patch(&self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap, protected: &HashSet<usize>) {
+        let candidates: Vec<usize> = (0..build_data.map.tiles.len())
+            .filter(|idx| self.can_cover(build_data, protected, *idx))
+            .collect();
+        if candidates.is_empty() { return; }
+
+        let center_idx = candidates[(rng.roll_dice(1, candidates.len() as i32) - 1) as usize];
+        let center = Point::new(center_idx as i32 % build_data.map.width, center_idx as i32 / build_data.map.width);
+        let radius = rng.roll_dice(1, self.max_radius - 1) + 1;
+
+        for y in center.y - radius ..= center.y + radius {
+            for x in center.x - radius ..= center.x + radius {
+                if x < 1 || x > build_data.map.width-2 || y < 1 || y > build_data.map.height-2 { continue; }
+                let idx = build_data.map.xy_idx(x, y);
+                if !self.can_cover(build_data, protected, idx) { continue; }
+
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(center, Point::new(x, y)) + rng.range(0, 2) as f32;
+                if distance <= radius as f32 {
+                    build_data.map.tiles[idx] = self.tile;
+                }
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
@@ -0,0 +1,148 @@
+use super::{MetaMapBuilder, BuilderMap, TileType, tile_walkable, door_possible};
+use rltk::RandomNumberGenerator;
+use std::collections::HashSet;
+
+/// Each kind of locked door, and the key which opens it.
+const LOCKS: [(&str, &str); 2] = [("Iron Door", "Iron Key"), ("Brass Door", "Brass Key")];
+
+/// The smallest area worth putting behind a lock.
+const MIN_LOCKED_AREA: i32 = 10;
+
+/// Locks some of the connections between rooms, and makes sure every locked door has a key.
+///
+/// Only chokepoints are locked, since a door you can walk around isn't much of a lock. Keys are
+/// always placed somewhere that can be reached from the start without going through any locked
+/// door, so the level can always be completed. Locked doors placed by earlier builders (such as
+/// vaults) are given keys too.
+pub struct LockedDoors {
+    max_locks: i32,
+}
+
+impl MetaMapBuilder for LockedDoors {
+    fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl LockedDoors {
+    #[allow(dead_code)]
+    pub fn new(max_locks: i32) -> Box<LockedDoors> {
+        Box::new(LockedDoors{ max_locks })
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let start_idx = match &build_data.starting_position {
+            None => return,
+            Some(start) => build_data.map.xy_idx(start.x, start.y),
+        };
+
+        if let Some(corridors) = build_data.corridors.clone() {
+            self.lock_chokepoints(rng, build_data, start_idx, &corridors);
+        }
+        self.place_keys(rng, build_data, start_idx);
+        build_data.take_snapshot();
+    }
+
+    /// Returns the tiles which currently have a locked door due to spawn on them.
+    fn locked_tiles(&self, build_data: &BuilderMap) -> Vec<usize> {
+        build_data.spawn_list.iter()
+            .filter(|(_idx, name)| LOCKS.iter().any(|(door, _key)| name == door))
+            .map(|(idx, _name)| *idx)
+            .collect()
+    }
+
+    /// Returns the distance of every tile from the starting position, treating locked doors as walls.
+    fn distances(&self, build_data: &BuilderMap, start_idx: usize, locked: &[usize]) -> Vec<f32> {
+        let mut map = build_data.map.clone();
+        for idx in locked.iter() {
+            map.tiles[*idx] = TileType::Wall;
+        }
+        map.populate_blocked();
+        let map_starts: Vec<usize> = vec![start_idx];
+        let dijkstra_map = rltk::DijkstraMap::new(map.width as usize, map.height as usize, &map_starts, &map, 1000.0);
+        dijkstra_map.map
+    }
+
+    /// Locks corridor ends which cut off a decent chunk of the map.
+    fn lock_chokepoints(&self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap, start_idx: usize, corridors: &[Vec<usize>]) {
+        let mut n_locks = 0;
+        for hall in corridors.iter() {
+            for end in [hall.first(), hall.last()].iter() {
+                if n_locks >= self.max_locks { return; }
+                let idx = match end {
+                    None => continue,
+                    Some(idx) => **idx,
+                };
+                if idx == start_idx || rng.roll_dice(1, 2) == 1 { continue; }
+
+                // Ordinary doors can be upgraded to locked ones
+                let has_door = build_data.spawn_list.iter().any(|(i, name)| *i == idx && name == "Door");
+                if !has_door && !door_possible(build_data, idx) { continue; }
+
+                let mut locked = self.locked_tiles(build_data);
+                let before = self.distances(build_data, start_idx, &locked);
+                if before[idx] == f32::MAX { continue; } // Already behind another lock
+                locked.push(idx);
+                let after = self.distances(build_data, start_idx, &locked);
+
+                let mut cut_off = 0;
+                for (i, tile) in build_data.map.tiles.iter().enumerate() {
+                    if i != idx && tile_walkable(*tile) && before[i] != f32::MAX && after[i] == f32::MAX {
+                        cut_off += 1;
+                    }
+                }
+                if cut_off < MIN_LOCKED_AREA { continue; }
+
+                build_data.spawn_list.retain(|(i, name)| !(*i == idx && name == "Door"));
+                let (door, _key) = LOCKS[(rng.roll_dice(1, LOCKS.len() as i32) - 1) as usize];
+                build_data.spawn_list.push((idx, door.to_string()));
+                n_locks += 1;
+            }
+        }
+    }
+
+    /// Places a key for every kind of locked door on the map, on the near side of all the locks.
+    fn place_keys(&self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap, start_idx: usize) {
+        let locked = self.locked_tiles(build_data);
+        if locked.is_empty() { return; }
+        let distances = self.distances(build_data, start_idx, &locked);
+
+        for (door, key) in LOCKS.iter() {
+            let needs_key = build_data.spawn_list.iter().any(|(_idx, name)| name == door);
+            let has_key = build_data.spawn_list.iter().any(|(_idx, name)| name == key);
+            if !needs_key || has_key { continue; }
+
+            let occupied: HashSet<usize> = build_data.spawn_list.iter().map(|(idx, _name)| *idx).collect();
+            let map = &build_data.map;
+            let free = |idx: usize| {
+                idx != start_idx && tile_walkable(map.tiles[idx]) && distances[idx] != f32::MAX && !occupied.contains(&idx)
+            };
+
+            // Prefer to leave the key in a room other than the one we start in
+            let mut candidates: Vec<usize> = Vec::new();
+            if let Some(rooms) = &build_data.rooms {
+                let start_x = start_idx as i32 % map.width;
+                let start_y = start_idx as i32 / map.width;
+                for room in rooms.iter() {
+                    if start_x >= room.x1 && start_x <= room.x2 && start_y >= room.y1 && start_y <= room.y2 { continue; }
+                    for y in room.y1 ..= room.y2 {
+                        for x in room.x1 ..= room.x2 {
+                            if x < 0 || x > map.width-1 || y < 0 || y > map.height-1 { continue; }
+                            let idx = map.xy_idx(x, y);
+                            if free(idx) { candidates.push(idx); }
+                        }
+                    }
+                }
+            }
+            if candidates.is_empty() {
+                candidates = (0..map.tiles.len()).filter(|idx| free(*idx)).collect();
+            }
+            if candidates.is_empty() {
+                candidates.push(start_idx);
+            }
+
+            let idx = candidates[(rng.roll_dice(1, candidates.len() as i32) - 1) as usize];
+            build_data.spawn_list.push((idx, key.to_string()));
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
     Map, Rect, TileType, Position, World, spawner,
-    tile_walkable, OPTIONS
+    tile_walkable, tile_hazardous, tile_has_flag, OPTIONS
 };
 mod simple_map;
 use simple_map::SimpleMapBuilder;
@@ -53,7 +53,13 @@ use room_corridor_spawner::CorridorSpawner;
 mod room_sorter;
 use room_sorter::*;
 mod door_placement;
-use door_placement::DoorPlacement;
+use door_placement::{DoorPlacement, door_possible};
+mod room_themes;
+use room_themes::RoomThemes;
+mod locked_doors;
+use locked_doors::LockedDoors;
+mod secret_doors;
+use secret_doors::SecretDoors;
 
 // Non-room-based meta builders
 mod area_starting_points;
@@ -64,6 +70,12 @@ mod distant_exit;
 use distant_exit::DistantExit;
 mod cull_unreachable;
 use cull_unreachable::CullUnreachable;
+mod rivers_lakes;
+use rivers_lakes::RiversAndLakes;
+mod hazards;
+use hazards::Hazards;
+mod fog;
+use fog::FogBanks;
 
 mod waveform_collapse;
 use waveform_collapse::*;
@@ -102,6 +114,8 @@ pub struct BuilderChain {
     starter: Option<Box<dyn InitialMapBuilder>>,
     /// Further builders which modify the initial map
     builders: Vec<Box<dyn MetaMapBuilder>>,
+    /// The visual theme the finished map is drawn with
+    theme: Option<String>,
     pub build_data: BuilderMap,
 }
 
@@ -110,6 +124,7 @@ impl BuilderChain {
         BuilderChain {
             starter: None,
             builders: Vec::new(),
+            theme: None,
             build_data: BuilderMap {
                 spawn_list: Vec::new(),
                 map: Map::new(new_depth, width, height, name),
@@ -137,9 +152,22 @@ impl BuilderChain {
         self.builders.push(metabuilder);
     }
 
+    /// Sets the visual theme (from the raws' tile themes) which the map will be drawn with.
+    pub fn with_theme<S: ToString>(&mut self, theme: S) {
+        self.theme = Some(theme.to_string());
+    }
+
+    /// Builders are free to replace the map, so the theme is reapplied after each one.
+    fn apply_theme(&mut self) {
+        if let Some(theme) = &self.theme {
+            self.build_data.map.theme = theme.clone();
+        }
+    }
+
     /// Build the map, by calling the initial builder, and then
     /// the metabuilders in order
     pub fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator) {
+        self.apply_theme();
         match &mut self.starter {
             None => panic!("Cannot run a map builder chain without a starting build system!"),
             Some(starter) => {
@@ -147,10 +175,12 @@ impl BuilderChain {
                 starter.build_map(rng, &mut self.build_data);
             }
         }
+        self.apply_theme();
 
         // Build additional layers in turn
-        for metabuilder in self.builders.iter_mut() {
-            metabuilder.build_map(rng, &mut self.build_data);
+        for i in 0..self.builders.len() {
+            self.builders[i].build_map(rng, &mut self.build_data);
+            self.apply_theme();
         }
     }
 
@@ -261,6 +291,8 @@ fn random_room_builder(rng: &mut rltk::RandomNumberGenerator, builder: &mut Buil
         1 => builder.with(RoomBasedSpawner::new()),
         _ => builder.with(VoronoiSpawning::new()),
     }
+
+    builder.with(RoomThemes::new());
 }
 
 fn random_shape_builder(rng: &mut rltk::RandomNumberGenerator, builder: &mut BuilderChain) {
@@ -295,11 +327,30 @@ fn random_shape_builder(rng: &mut rltk::RandomNumberGenerator, builder: &mut Bui
     builder.with(DistantExit::new());
 }
 
-/// Randomly generate a map
+/// Picks the size of the level at a depth. The town and the forest are a fixed size; below them,
+/// levels get bigger the deeper you go, with the odd sprawling cavern and small, tight crypt.
+pub fn level_size(new_depth: i32, rng: &mut rltk::RandomNumberGenerator) -> (i32, i32) {
+    if new_depth < 3 { return (80, 44); }
+    let growth = i32::min(new_depth * 4, 40);
+    match rng.roll_dice(1, 6) {
+        1 => (rng.range(32, 48), rng.range(24, 34)),
+        2 | 3 => (rng.range(80, 120) + growth, rng.range(44, 64) + growth / 2),
+        _ => (80 + rng.range(0, growth + 1), 44 + rng.range(0, growth / 2 + 1)),
+    }
+}
+
+/// Randomly generate a map. Small maps are always rooms and corridors, and sprawling ones always
+/// caverns; anything in between could be either.
 pub fn random_builder(new_depth: i32, rng: &mut rltk::RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
+    let type_roll = if width * height < 80 * 44 {
+        1
+    } else if width * height > 100 * 54 {
+        2
+    } else {
+        rng.roll_dice(1, 2)
+    };
     let mut builder = BuilderChain::new(new_depth, width, height, "New Map");
     // /*
-    let type_roll = rng.roll_dice(1, 2);
     match type_roll {
         1 => random_room_builder(rng, &mut builder),
         _ => random_shape_builder(rng, &mut builder)
@@ -317,12 +368,38 @@ pub fn random_builder(new_depth: i32, rng: &mut rltk::RandomNumberGenerator, wid
         builder.with(DistantExit::new());
     }
 
+    if type_roll != 1 {
+        // Caverns sometimes have underground rivers and lakes running through them
+        match rng.roll_dice(1, 6) {
+            1 => builder.with(RiversAndLakes::rivers()),
+            2 => {
+                builder.with(RiversAndLakes::lakes());
+                // Some lakes are stagnant, fog-bound swamps
+                if rng.roll_dice(1, 3) == 1 { builder.with(FogBanks::new()); }
+            }
+            _ => {}
+        }
+
+        // Deeper down they can be split by chasms, frozen over or flooded with lava
+        match rng.roll_dice(1, 6) {
+            1 if new_depth >= 3 => builder.with(Hazards::chasms()),
+            2 if new_depth >= 4 => builder.with(Hazards::ice()),
+            3 if new_depth >= 6 => builder.with(Hazards::lava()),
+            _ => {}
+        }
+    }
+
     if rng.roll_dice(1, 20) == 1 {
         builder.with(PrefabBuilder::sectional(prefab_builder::prefab_sections::UNDERGROUND_FORT));
     }
 
+    if type_roll == 1 && rng.roll_dice(1, 3) == 1 {
+        builder.with(SecretDoors::new());
+    }
     builder.with(DoorPlacement::new());
     builder.with(PrefabBuilder::vaults());
+    let n_locks = if type_roll == 1 { rng.roll_dice(1, 3) - 1 } else { 0 };
+    builder.with(LockedDoors::new(n_locks));
     // */
 
     builder
@@ -109,6 +109,10 @@ impl PrefabBuilder {
                 build_data.map.tiles[idx] = TileType::Floor;
                 build_data.spawn_list.push((idx, "Health Potion".to_string()));
             }
+            'L' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Iron Door".to_string()));
+            }
             _ => {
                 rltk::console::log(format!("Unknown glyph loading map: {}", (ch as u8) as char));
             }
@@ -175,6 +179,9 @@ impl PrefabBuilder {
     pub fn apply_sectional(&mut self, section: &prefab_sections::PrefabSection, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
         use prefab_sections::*;
 
+        // Small maps might not have room for the section at all
+        if section.width as i32 > build_data.map.width - 1 || section.height as i32 > build_data.map.height - 1 { return; }
+
         let string_vec = PrefabBuilder::read_ascii_to_vec(section.template);
 
         // Place the new section
@@ -223,7 +230,7 @@ impl PrefabBuilder {
         if vault_roll < 4 { return; }
 
         // TODO: This is a placeholder & will be moved out of this function
-        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE];
+        let master_vault_list = [TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE, LOCKED_STOREROOM];
 
         // Filter the vault list down to ones that are applicable at the current depth
         let mut possible_vaults: Vec<&PrefabRoom> = master_vault_list
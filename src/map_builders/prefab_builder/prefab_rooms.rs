@@ -63,3 +63,22 @@ const CHECKERBOARD_MAP: &str = "
  ^# # 
       
 ";
+
+#[allow(dead_code)]
+pub const LOCKED_STOREROOM: PrefabRoom = PrefabRoom {
+    template: LOCKED_STOREROOM_MAP,
+    width: 7,
+    height: 6,
+    first_depth: 3,
+    last_depth: 100,
+};
+
+#[allow(dead_code)]
+const LOCKED_STOREROOM_MAP: &str = "
+       
+ ##L## 
+ #!%!# 
+ #%!%# 
+ ##### 
+       
+";
@@ -0,0 +1,196 @@
+use super::{MetaMapBuilder, BuilderMap, TileType, tile_walkable, lay_crossing, cull_cut_off_spawns};
+use rltk::{RandomNumberGenerator, Point};
+use std::collections::HashSet;
+
+/// Carves meandering rivers and fills open basins with lakes on an existing map.
+///
+/// Rivers cut through anything except the stairs and the starting tile, and become bridges where
+/// they cross a road. Lakes only flood walkable tiles, so they fill caverns and clearings rather
+/// than eating into walls. Once the water is in place, the start and exit are re-connected with
+/// bridges if the water has cut them off.
+pub struct RiversAndLakes {
+    n_rivers: i32,
+    n_lakes: i32,
+}
+
+impl MetaMapBuilder for RiversAndLakes {
+    fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl RiversAndLakes {
+    #[allow(dead_code)]
+    pub fn new(n_rivers: i32, n_lakes: i32) -> Box<RiversAndLakes> {
+        Box::new(RiversAndLakes{ n_rivers, n_lakes })
+    }
+
+    /// A single river and a couple of lakes.
+    #[allow(dead_code)]
+    pub fn wetlands() -> Box<RiversAndLakes> {
+        RiversAndLakes::new(1, 2)
+    }
+
+    /// Rivers only, which suits narrow cave systems where there's no room for lakes.
+    #[allow(dead_code)]
+    pub fn rivers() -> Box<RiversAndLakes> {
+        RiversAndLakes::new(2, 0)
+    }
+
+    /// Lakes only.
+    #[allow(dead_code)]
+    pub fn lakes() -> Box<RiversAndLakes> {
+        RiversAndLakes::new(0, 3)
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let protected = self.protected_tiles(build_data);
+
+        for _i in 0..self.n_lakes {
+            self.lake(rng, build_data, &protected);
+        }
+        for _i in 0..self.n_rivers {
+            self.river(rng, build_data, &protected);
+        }
+
+        lay_crossing(build_data, TileType::SwimmableWater, TileType::Bridge);
+        cull_cut_off_spawns(build_data);
+        build_data.take_snapshot();
+    }
+
+    /// Tiles which water must never be placed on: the starting position and any stairs.
+    fn protected_tiles(&self, build_data: &BuilderMap) -> HashSet<usize> {
+        let mut protected = HashSet::new();
+        if let Some(start) = &build_data.starting_position {
+            protected.insert(build_data.map.xy_idx(start.x, start.y));
+        }
+        for (idx, tile) in build_data.map.tiles.iter().enumerate() {
+            if *tile == TileType::DownStairs {
+                protected.insert(idx);
+            }
+        }
+        protected
+    }
+
+    /// Turns a single tile into water, respecting protected tiles and roads.
+    ///
+    /// Roads become bridges rather than water, so that paths stay walkable. Deep water never gets
+    /// downgraded to shallow water by a neighbouring bank.
+    fn flood(&self, build_data: &mut BuilderMap, protected: &HashSet<usize>, x: i32, y: i32, deep: bool) {
+        if x < 1 || x > build_data.map.width-2 || y < 1 || y > build_data.map.height-2 { return; }
+        let idx = build_data.map.xy_idx(x, y);
+        if protected.contains(&idx) { return; }
+
+        match build_data.map.tiles[idx] {
+            TileType::Road | TileType::Bridge => build_data.map.tiles[idx] = TileType::Bridge,
+            TileType::SwimmableWater => {}
+            _ => {
+                build_data.map.tiles[idx] = if deep { TileType::SwimmableWater } else { TileType::ShallowWater };
+            }
+        }
+    }
+
+    /// Finds a basin (a spot with lots of open floor around it) and fills it with a lake.
+    fn lake(&self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap, protected: &HashSet<usize>) {
+        let radius = rng.roll_dice(1, 4) + 2;
+
+        let candidates: Vec<usize> = build_data.map.tiles.iter()
+            .enumerate()
+            .filter(|(idx, tile)| tile_walkable(**tile) && !protected.contains(idx))
+            .map(|(idx, _tile)| idx)
+            .collect();
+        if candidates.is_empty() { return; }
+
+        // Sample a handful of candidates, and keep whichever is most open.
+        let mut best: Option<(usize, i32)> = None;
+        for _i in 0..20 {
+            let idx = candidates[(rng.roll_dice(1, candidates.len() as i32) - 1) as usize];
+            let openness = self.count_walkable(build_data, idx, radius);
+            if best.is_none() || openness > best.unwrap().1 {
+                best = Some((idx, openness));
+            }
+        }
+        let (center_idx, _openness) = best.unwrap();
+        let center = Point::new(center_idx as i32 % build_data.map.width, center_idx as i32 / build_data.map.width);
+
+        for y in center.y - radius ..= center.y + radius {
+            for x in center.x - radius ..= center.x + radius {
+                if x < 1 || x > build_data.map.width-2 || y < 1 || y > build_data.map.height-2 { continue; }
+                let idx = build_data.map.xy_idx(x, y);
+                if !tile_walkable(build_data.map.tiles[idx]) { continue; } // Lakes only fill basins
+
+                // A little jitter keeps the shoreline from being perfectly circular
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(center, Point::new(x, y)) + rng.range(0, 2) as f32;
+                if distance < radius as f32 - 1.0 {
+                    self.flood(build_data, protected, x, y, true);
+                } else if distance <= radius as f32 {
+                    self.flood(build_data, protected, x, y, false);
+                }
+            }
+        }
+        build_data.take_snapshot();
+    }
+
+    fn count_walkable(&self, build_data: &BuilderMap, idx: usize, radius: i32) -> i32 {
+        let cx = idx as i32 % build_data.map.width;
+        let cy = idx as i32 / build_data.map.width;
+        let mut count = 0;
+        for y in cy - radius ..= cy + radius {
+            for x in cx - radius ..= cx + radius {
+                if x < 0 || x > build_data.map.width-1 || y < 0 || y > build_data.map.height-1 { continue; }
+                if tile_walkable(build_data.map.tiles[build_data.map.xy_idx(x, y)]) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Carves a river from one edge of the map to the opposite edge.
+    ///
+    /// The river wanders: each step it usually heads towards its destination, but sometimes drifts
+    /// sideways instead. It has a deep channel with shallow banks on either side.
+    fn river(&self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap, protected: &HashSet<usize>) {
+        let w = build_data.map.width;
+        let h = build_data.map.height;
+        let north_south = rng.roll_dice(1, 2) == 1;
+        let (mut x, mut y, end_x, end_y) = if north_south {
+            (rng.roll_dice(1, w-2), 1, rng.roll_dice(1, w-2), h-2)
+        } else {
+            (1, rng.roll_dice(1, h-2), w-2, rng.roll_dice(1, h-2))
+        };
+
+        let mut steps = 0;
+        while (x != end_x || y != end_y) && steps < w * h {
+            steps += 1;
+
+            self.flood(build_data, protected, x, y, true);
+            self.flood(build_data, protected, x-1, y, false);
+            self.flood(build_data, protected, x+1, y, false);
+            self.flood(build_data, protected, x, y-1, false);
+            self.flood(build_data, protected, x, y+1, false);
+
+            if rng.roll_dice(1, 3) > 1 {
+                // Head downstream
+                let dx = end_x - x;
+                let dy = end_y - y;
+                if i32::abs(dx) > i32::abs(dy) || (dx != 0 && rng.roll_dice(1, 2) == 1) {
+                    x += i32::signum(dx);
+                } else {
+                    y += i32::signum(dy);
+                }
+            } else {
+                // Meander
+                match rng.roll_dice(1, 4) {
+                    1 => x -= 1,
+                    2 => x += 1,
+                    3 => y -= 1,
+                    _ => y += 1,
+                }
+                x = i32::max(1, i32::min(w-2, x));
+                y = i32::max(1, i32::min(h-2, y));
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
@@ -0,0 +1,150 @@
+use super::{MetaMapBuilder, BuilderMap, TileType, Rect, tile_walkable};
+use crate::raws::{RAWS, get_room_theme, get_room_contents};
+use rltk::RandomNumberGenerator;
+use std::collections::HashSet;
+
+/// Turns some of the rooms on a map into barracks, storerooms, shrines and the like.
+///
+/// Each room is tagged by where it is on the map ("edge" or "interior", and "near" or "far" from
+/// the start), and a theme which suits its size and placement is picked from the raws. Whatever
+/// was due to spawn in the room is replaced with the theme's furniture, mobs and loot. Furniture
+/// is pushed up against the walls, away from the entrances, so it never blocks the way through.
+pub struct RoomThemes {}
+
+impl MetaMapBuilder for RoomThemes {
+    fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl RoomThemes {
+    #[allow(dead_code)]
+    pub fn new() -> Box<RoomThemes> {
+        Box::new(RoomThemes{})
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let rooms = match &build_data.rooms {
+            None => return,
+            Some(rooms) => rooms.clone(),
+        };
+        let start_idx = match &build_data.starting_position {
+            None => return,
+            Some(start) => build_data.map.xy_idx(start.x, start.y),
+        };
+
+        build_data.map.populate_blocked();
+        let map_starts: Vec<usize> = vec![start_idx];
+        let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &map_starts, &build_data.map, 1000.0);
+
+        // How far each room is from the start, so that we can tell which rooms are near and far
+        let room_distance: Vec<f32> = rooms.iter()
+            .map(|room| {
+                self.interior(build_data, room).iter()
+                    .map(|idx| dijkstra_map.map[*idx])
+                    .fold(f32::MAX, f32::min)
+            })
+            .collect();
+        let mut reachable: Vec<f32> = room_distance.iter().filter(|d| **d < f32::MAX).copied().collect();
+        reachable.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        if reachable.is_empty() { return; }
+        let near_cutoff = reachable[reachable.len() / 3];
+        let far_cutoff = reachable[(reachable.len() * 2) / 3];
+
+        for (i, room) in rooms.iter().enumerate() {
+            let interior = self.interior(build_data, room);
+            if interior.contains(&start_idx) || interior.iter().any(|idx| build_data.map.tiles[*idx] == TileType::DownStairs) { continue; }
+            if room.x2 - room.x1 < 3 || room.y2 - room.y1 < 3 { continue; } // Too cramped for furniture
+            if rng.roll_dice(1, 2) == 1 { continue; }
+
+            let mut placement = vec![if self.is_edge(build_data, room) { "edge" } else { "interior" }];
+            if room_distance[i] <= near_cutoff { placement.push("near"); }
+            if room_distance[i] < f32::MAX && room_distance[i] >= far_cutoff { placement.push("far"); }
+
+            let area = (room.x2 - room.x1) * (room.y2 - room.y1);
+            let theme = get_room_theme(&RAWS.lock().unwrap(), rng, build_data.map.depth, area, &placement);
+            if let Some(theme) = theme {
+                self.furnish(rng, build_data, room, &theme, start_idx);
+            }
+        }
+        build_data.take_snapshot();
+    }
+
+    /// Returns the floor tiles inside a room.
+    fn interior(&self, build_data: &BuilderMap, room: &Rect) -> Vec<usize> {
+        let mut tiles = Vec::new();
+        for y in room.y1 + 1 ..= room.y2 {
+            for x in room.x1 + 1 ..= room.x2 {
+                if x < 1 || x > build_data.map.width-2 || y < 1 || y > build_data.map.height-2 { continue; }
+                let idx = build_data.map.xy_idx(x, y);
+                if build_data.map.tiles[idx] == TileType::Floor || build_data.map.tiles[idx] == TileType::DownStairs {
+                    tiles.push(idx);
+                }
+            }
+        }
+        tiles
+    }
+
+    /// Is the room within the outer sixth of the map?
+    fn is_edge(&self, build_data: &BuilderMap, room: &Rect) -> bool {
+        let margin_x = build_data.map.width / 6;
+        let margin_y = build_data.map.height / 6;
+        room.x1 <= margin_x || room.x2 >= build_data.map.width - margin_x
+            || room.y1 <= margin_y || room.y2 >= build_data.map.height - margin_y
+    }
+
+    /// Can furniture go on this tile? It has to be against a wall, and not next to anywhere you
+    /// might walk into the room from. Rooms aren't always neat rectangles, so it also mustn't cut
+    /// the floor around it in two, counting any furniture already placed as solid.
+    fn furniture_possible(&self, build_data: &BuilderMap, interior: &HashSet<usize>, placed: &HashSet<usize>, idx: usize) -> bool {
+        let w = build_data.map.width as usize;
+        let mut against_wall = false;
+        for neighbour in [idx - 1, idx + 1, idx - w, idx + w].iter() {
+            match build_data.map.tiles[*neighbour] {
+                TileType::Wall => against_wall = true,
+                _ => if !interior.contains(neighbour) { return false; }
+            }
+        }
+        if !against_wall { return false; }
+
+        // Walk around the eight surrounding tiles; if the open ones form more than one run, this
+        // tile is the only thing joining them.
+        let ring = [idx - w - 1, idx - w, idx - w + 1, idx + 1, idx + w + 1, idx + w, idx + w - 1, idx - 1];
+        let open: Vec<bool> = ring.iter()
+            .map(|i| tile_walkable(build_data.map.tiles[*i]) && !placed.contains(i))
+            .collect();
+        let runs = (0..open.len()).filter(|i| open[*i] && !open[(i + 1) % open.len()]).count();
+        runs <= 1
+    }
+
+    /// Clears out whatever was going to spawn in a room, and fills it with the theme's contents.
+    fn furnish(&self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap, room: &Rect, theme: &str, start_idx: usize) {
+        let interior: HashSet<usize> = self.interior(build_data, room).into_iter().collect();
+        build_data.spawn_list.retain(|(idx, _name)| !interior.contains(idx));
+
+        let mut free: Vec<usize> = interior.iter()
+            .filter(|idx| **idx != start_idx && build_data.map.tiles[**idx] == TileType::Floor)
+            .copied()
+            .collect();
+        free.sort_unstable(); // HashSet order isn't stable, and we want seeded maps to be repeatable
+        let mut placed: HashSet<usize> = HashSet::new();
+
+        let (furniture, others) = get_room_contents(&RAWS.lock().unwrap(), rng, theme);
+        for name in furniture.iter() {
+            let against_walls: Vec<usize> = free.iter()
+                .filter(|idx| self.furniture_possible(build_data, &interior, &placed, **idx))
+                .copied()
+                .collect();
+            if against_walls.is_empty() { break; }
+            let idx = against_walls[(rng.roll_dice(1, against_walls.len() as i32) - 1) as usize];
+            free.retain(|i| *i != idx);
+            placed.insert(idx);
+            build_data.spawn_list.push((idx, name.clone()));
+        }
+        for name in others.iter() {
+            if free.is_empty() { break; }
+            let idx = free.remove((rng.roll_dice(1, free.len() as i32) - 1) as usize);
+            build_data.spawn_list.push((idx, name.clone()));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use super::{MetaMapBuilder, BuilderMap, TileType, tile_walkable, door_possible};
+use rltk::RandomNumberGenerator;
+
+/// Hides the entrances to some dead-end parts of the map behind secret doors.
+///
+/// A corridor end is a dead-end connection if walling it off would cut off part of the map, but
+/// not the exit. These are turned into walls, with a hidden "Secret Door" prop which turns back
+/// into a door once the player finds it.
+pub struct SecretDoors {}
+
+impl MetaMapBuilder for SecretDoors {
+    fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl SecretDoors {
+    #[allow(dead_code)]
+    pub fn new() -> Box<SecretDoors> {
+        Box::new(SecretDoors{})
+    }
+
+    /// Returns the distance of every tile from the starting position.
+    fn distances(&self, build_data: &mut BuilderMap, start_idx: usize) -> Vec<f32> {
+        build_data.map.populate_blocked();
+        let map_starts: Vec<usize> = vec![start_idx];
+        let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &map_starts, &build_data.map, 1000.0);
+        dijkstra_map.map
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let corridors = match &build_data.corridors {
+            None => return, // Only corridors can have secret doors
+            Some(corridors) => corridors.clone(),
+        };
+        let start_idx = match &build_data.starting_position {
+            None => return,
+            Some(start) => build_data.map.xy_idx(start.x, start.y),
+        };
+        let exit_idx = match build_data.map.tiles.iter().position(|t| *t == TileType::DownStairs) {
+            None => return,
+            Some(idx) => idx,
+        };
+
+        let max_secrets = rng.roll_dice(1, 2);
+        let mut n_secrets = 0;
+        for hall in corridors.iter() {
+            for end in [hall.first(), hall.last()].iter() {
+                if n_secrets >= max_secrets { return; }
+                let idx = match end {
+                    None => continue,
+                    Some(idx) => **idx,
+                };
+                if idx == start_idx || !door_possible(build_data, idx) || rng.roll_dice(1, 2) == 1 { continue; }
+
+                let before = self.distances(build_data, start_idx);
+                build_data.map.tiles[idx] = TileType::Wall;
+                let after = self.distances(build_data, start_idx);
+
+                // Count how much of the map we've cut off by walling up this tile
+                let mut cut_off = 0;
+                for (i, tile) in build_data.map.tiles.iter().enumerate() {
+                    if tile_walkable(*tile) && before[i] != f32::MAX && after[i] == f32::MAX {
+                        cut_off += 1;
+                    }
+                }
+
+                if after[exit_idx] == f32::MAX || cut_off == 0 {
+                    // Either this is the way out, or it isn't a dead end - put it back
+                    build_data.map.tiles[idx] = TileType::Floor;
+                } else {
+                    build_data.spawn_list.push((idx, "Secret Door".to_string()));
+                    n_secrets += 1;
+                    build_data.take_snapshot();
+                }
+            }
+        }
+    }
+}
@@ -455,7 +455,7 @@ impl TownBuilder {
         build_data: &mut BuilderMap,
         rng: &mut rltk::RandomNumberGenerator
     ) {
-        let mut to_place: Vec<&str> = vec!["Alchemist", "Chemistry Set", "Dead Thing", "Chair", "Table"];
+        let mut to_place: Vec<&str> = vec!["Alchemist", "Chemistry Set", "Dead Thing", "Chair", "Table", "Alchemist's Fire", "Gas Bomb", "Healing Mist Flask"];
         self.random_building_spawn(building, build_data, rng, &mut to_place, 0);
     }
 
@@ -517,11 +517,12 @@ impl TownBuilder {
     fn spawn_townsfolk(&mut self, build_data: &mut BuilderMap, rng: &mut rltk::RandomNumberGenerator, available_building_tiles: &mut HashSet<usize>) {
         for idx in available_building_tiles.iter() {
             if rng.roll_dice(1, 10) == 1{
-                let roll = rng.roll_dice(1, 4);
+                let roll = rng.roll_dice(1, 5);
                 match roll {
                     1 => build_data.spawn_list.push((*idx, "Peasant".to_string())),
                     2 => build_data.spawn_list.push((*idx, "Drunk".to_string())),
                     3 => build_data.spawn_list.push((*idx, "Dock Worker".to_string())),
+                    4 => build_data.spawn_list.push((*idx, "Town Guard".to_string())),
                     _ => build_data.spawn_list.push((*idx, "Fisher".to_string())),
                 }
             }
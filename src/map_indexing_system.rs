@@ -1,5 +1,5 @@
 use specs::prelude::*;
-use super::{Map, Position, BlocksTile};
+use super::{Map, Position, BlocksTile, Door, Locked, Hidden};
 
 pub struct MapIndexingSystem {}
 
@@ -7,10 +7,13 @@ impl<'a> System<'a> for MapIndexingSystem {
     type SystemData = ( WriteExpect<'a, Map>,
                         ReadStorage<'a, Position>,
                         ReadStorage<'a, BlocksTile>,
-                        Entities<'a>,);
+                        Entities<'a>,
+                        ReadStorage<'a, Door>,
+                        ReadStorage<'a, Locked>,
+                        ReadStorage<'a, Hidden>,);
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, position, blockers, entities) = data;
+        let (mut map, position, blockers, entities, doors, locked, hidden) = data;
 
         map.populate_blocked(); // all walls are blocked
         map.clear_content_index(); // Clear the index of entities
@@ -35,5 +38,14 @@ impl<'a> System<'a> for MapIndexingSystem {
             let idx = map.xy_idx(position.x, position.y);
             map.blocked[idx] = true;
         }
+
+        // Doors which someone could open, if they knew how
+        map.closed_doors.clear();
+        for (position, door, _locked, _hidden) in (&position, &doors, !&locked, !&hidden).join() {
+            if !door.open {
+                let idx = map.xy_idx(position.x, position.y);
+                map.closed_doors.insert(idx);
+            }
+        }
     }
 }
\ No newline at end of file
@@ -1,22 +1,75 @@
 use specs::prelude::*;
+use specs::storage::MaskedStorage;
+use std::ops::Deref;
 use super::{
     Attributes, WantsToMelee, Name, SufferDamage, gamelog::GameLog,
     HungerClock, HungerState, particle_system::ParticleBuilder, Position,
     Skills, Pools, Skill, Equipped, MeleeWeapon, WeaponAttribute, EquipmentSlot,
-    Wearable, NaturalAttackDefense
+    Wearable, NaturalAttackDefense, DamageType, StatusEffects, StatusEffect, Shield,
+    Encumbrance, Awareness, Alertness, stealth_system::{NoiseBuilder, MELEE_NOISE},
 };
-use crate::{skill_bonus};
+use crate::{skill_bonus, improve_skills};
+
+/// Works out how hard a target is to hit, returning their armour class and how much of that comes
+/// from the armour they're wearing.
+pub fn armour_class<D: Deref<Target = MaskedStorage<Equipped>>>(
+    target: Entity,
+    equipped_items: &Storage<Equipped, D>,
+    wearables: &ReadStorage<Wearable>,
+    natural: &ReadStorage<NaturalAttackDefense>,
+    target_attributes: &Attributes,
+    target_skills: &Skills,
+) -> (i32, i32) {
+    let mut armour_item_bonus_f = 0.0;
+    for (wielded, armour) in (equipped_items, wearables).join() {
+        if wielded.owner == target {
+            armour_item_bonus_f += armour.armour_class;
+        }
+    }
+    let base_armour_class = match natural.get(target) {
+        None => 10,
+        Some(nat) => nat.armour_class.unwrap_or(10)
+    };
+    let armour_quickness_bonus = target_attributes.quickness.bonus;
+    let armour_skill_bonus = skill_bonus(Skill::Defense, target_skills);
+    let armour_item_bonus = armour_item_bonus_f as i32;
+    (base_armour_class + armour_quickness_bonus + armour_skill_bonus + armour_item_bonus, armour_item_bonus)
+}
+
+/// Gives a defender with a shield the chance to block a blow (or a shot) which got through. Their
+/// defense skill and might each add 5% to the shield's own block chance. Returns the shield's name
+/// and how much damage it stops.
+pub fn shield_block<D: Deref<Target = MaskedStorage<Equipped>>>(
+    target: Entity,
+    equipped_items: &Storage<Equipped, D>,
+    shields: &ReadStorage<Shield>,
+    names: &ReadStorage<Name>,
+    target_attributes: &Attributes,
+    target_skills: &Skills,
+    rng: &mut rltk::RandomNumberGenerator,
+) -> Option<(String, i32)> {
+    for (wielded, shield, shield_name) in (equipped_items, shields, names).join() {
+        if wielded.owner == target && wielded.slot == EquipmentSlot::Shield {
+            let chance = shield.block_chance + (skill_bonus(Skill::Defense, target_skills) + target_attributes.might.bonus) * 5;
+            if rng.roll_dice(1, 100) <= chance {
+                return Some((shield_name.name.clone(), shield.block_amount));
+            }
+        }
+    }
+    None
+}
 
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         Entities<'a>,
         WriteExpect<'a, GameLog>,
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, Attributes>,
-        ReadStorage<'a, Skills>,
+        WriteStorage<'a, Skills>,
         WriteStorage<'a, SufferDamage>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
@@ -27,15 +80,25 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, MeleeWeapon>,
         ReadStorage<'a, Wearable>,
         ReadStorage<'a, NaturalAttackDefense>,
+        WriteStorage<'a, StatusEffects>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Shield>,
+        ReadStorage<'a, Encumbrance>,
+        WriteStorage<'a, Awareness>,
+        WriteExpect<'a, NoiseBuilder>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
-            entities, mut log, mut wants_melee, names, attributes, skills,
+            entities, mut log, mut wants_melee, names, attributes, mut skills,
             mut inflict_damage, mut particle_builder, positions, hunger_clock,
-            pools, mut rng, equipped_items, meleeweapons, wearables, natural
+            pools, mut rng, equipped_items, meleeweapons, wearables, natural, mut status_effects,
+            player_entity, shields, encumbrance, mut awareness, mut noise,
         ) = data;
 
+        // Skills which got some practice this turn
+        let mut practised: Vec<(Entity, Skill)> = Vec::new();
+
         for (entity, wants_melee, name, attacker_attributes, attacker_skills, attacker_pools) in (&entities, &wants_melee, &names, &attributes, &skills, &pools).join() {
             // Are the attacker and defender both alive? Only attack if they are
             let target_pools = pools.get(wants_melee.target).unwrap();
@@ -46,17 +109,22 @@ impl<'a> System<'a> for MeleeCombatSystem {
 
                 let mut weapon_info = MeleeWeapon{
                     attribute: WeaponAttribute::Might,
+                    damage_type: DamageType::Bludgeoning,
                     hit_bonus: 0,
                     damage_n_dice: 1,
                     damage_die_type: 4,
                     damage_bonus: 0
                 };
+                // Venomous bites and the like
+                let mut inflicts: Vec<(StatusEffect, i32)> = Vec::new();
 
                 if let Some(nat) = natural.get(entity) {
                     if !nat.attacks.is_empty() {
                         // Choose a random natural attack from those available to the monster
                         let attack_index = if nat.attacks.len()==1 { 0 } else { rng.roll_dice(1, nat.attacks.len() as i32) as usize -1 };
                         weapon_info.hit_bonus = nat.attacks[attack_index].hit_bonus;
+                        weapon_info.damage_type = nat.attacks[attack_index].damage_type;
+                        inflicts = nat.attacks[attack_index].inflicts.clone();
                         weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
                         weapon_info.damage_die_type = nat.attacks[attack_index].damage_die_type;
                         weapon_info.damage_bonus = nat.attacks[attack_index].damage_bonus;
@@ -66,6 +134,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 for (wielded, melee) in (&equipped_items, &meleeweapons).join() {
                     if wielded.owner == entity && wielded.slot == EquipmentSlot::Melee {
                         weapon_info = melee.clone();
+                        inflicts.clear();
                     }
                 }
 
@@ -82,23 +151,28 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         status_hit_bonus += 1;
                     }
                 }
+                if let Some(burden) = encumbrance.get(entity) { // Struggling under a heavy load
+                    status_hit_bonus -= burden.state.hit_penalty();
+                }
+                // Catching someone unawares makes them much easier to hit, and hurts more
+                let sneak_attack = awareness.get(wants_melee.target).is_some_and(|a| a.state != Alertness::Alert);
+                if sneak_attack {
+                    status_hit_bonus += 4;
+                }
                 let modified_hit_roll = natural_roll + attribute_hit_bonus + skill_hit_bonus + status_hit_bonus + weapon_hit_bonus;
 
-                // Calculate defender's AC
-                let mut armour_item_bonus_f = 0.0;
-                for (wielded, armour) in (&equipped_items, &wearables).join() {
-                    if wielded.owner == wants_melee.target {
-                        armour_item_bonus_f += armour.armour_class;
-                    }
+                // The player fighting is noisy, and whoever was attacked certainly knows about it now
+                if let (true, Some(pos)) = (entity == *player_entity, positions.get(entity)) {
+                    noise.make(pos.x, pos.y, MELEE_NOISE);
+                }
+                if let Some(target_awareness) = awareness.get_mut(wants_melee.target) {
+                    target_awareness.state = Alertness::Alert;
                 }
-                let base_armour_class = match natural.get(wants_melee.target) {
-                    None => 10,
-                    Some(nat) => nat.armour_class.unwrap_or(10)
-                };
-                let armour_quickness_bonus = target_attributes.quickness.bonus;
-                let armour_skill_bonus = skill_bonus(Skill::Defense, &*target_skills);
-                let armour_item_bonus = armour_item_bonus_f as i32;
-                let armour_class = base_armour_class + armour_quickness_bonus + armour_skill_bonus + armour_item_bonus;
+
+                // Calculate defender's AC
+                let (armour_class, armour_item_bonus) = armour_class(
+                    wants_melee.target, &equipped_items, &wearables, &natural, target_attributes, target_skills
+                );
 
                 // Determine if the attack hits
                 if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll >= armour_class) {
@@ -108,13 +182,42 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         log.entries.push("Critical hit!!".to_string());
                         base_damage += rng.roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
                     }
+                    if sneak_attack {
+                        log.entries.push(format!("{} catches {} unawares!", name.name, target_name.name));
+                        base_damage += rng.roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
+                        practised.push((entity, Skill::Stealth));
+                    }
                     let attr_damage_bonus = attacker_attributes.might.bonus;
                     let skill_damage_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
                     let weapon_damage_bonus = weapon_info.damage_bonus;
 
-                    let damage = i32::max(0, base_damage + attr_damage_bonus + skill_hit_bonus + skill_damage_bonus + weapon_damage_bonus);
-                    SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                    let mut damage = i32::max(0, base_damage + attr_damage_bonus + skill_hit_bonus + skill_damage_bonus + weapon_damage_bonus);
+                    practised.push((entity, Skill::Melee));
+
+                    // A shield can still catch the blow, unless it was a critical
+                    if natural_roll != 20 {
+                        if let Some((shield_name, block_amount)) = shield_block(
+                            wants_melee.target, &equipped_items, &shields, &names, target_attributes, target_skills, &mut rng
+                        ) {
+                            practised.push((wants_melee.target, Skill::Defense));
+                            damage = i32::max(0, damage - block_amount);
+                            if damage == 0 {
+                                log.entries.push(format!("{} attacks {}, but is blocked by the {}!", name.name, target_name.name, shield_name));
+                                if let Some(pos) = positions.get(wants_melee.target) {
+                                    particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::CYAN), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
+                                }
+                                continue;
+                            }
+                            log.entries.push(format!("{} partly blocks the blow with the {}.", target_name.name, shield_name));
+                        }
+                    }
+
+                    SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage, weapon_info.damage_type, entity == *player_entity);
                     log.entries.push(format!("{} hits {} for {} damage!", name.name, target_name.name, damage));
+                    for (effect, turns) in inflicts.iter() {
+                        StatusEffects::apply(&mut status_effects, wants_melee.target, *effect, *turns);
+                        log.entries.push(format!("{} is {}!", target_name.name, effect.describe().to_lowercase()));
+                    }
                     if let Some(pos) = positions.get(wants_melee.target) {
                         particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
                     }
@@ -126,6 +229,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     }
                 } else {
                     // Miss
+                    practised.push((wants_melee.target, Skill::Defense));
                     if modified_hit_roll > armour_class - armour_item_bonus {
                         // The attack wouldn't have hit even without armour, so it's a dodge
                         log.entries.push(format!("{} attacks {}, but {} dodges!", name.name, target_name.name, target_name.name));
@@ -140,5 +244,6 @@ impl<'a> System<'a> for MeleeCombatSystem {
         }
 
         wants_melee.clear();
+        improve_skills(&practised, &mut skills, *player_entity, &mut log);
     }
 }
\ No newline at end of file
@@ -0,0 +1,101 @@
+use specs::prelude::*;
+use super::{
+    Map, Position, Hidden, Name, gamelog::GameLog, Attributes, WantsToSearch,
+    SecretDoor, Door, TileType, Viewshed, particle_system::ParticleBuilder, EntryTrigger, Skills,
+    trap_spotting_bonus,
+};
+use crate::{perception_check, improve_skills, Skill};
+
+/// How far away an active search can find things
+const SEARCH_RADIUS: i32 = 3;
+/// Difficulty of finding something when actively searching for it
+pub const SEARCH_DC: i32 = 10;
+/// Difficulty of noticing something without looking for it
+pub const PASSIVE_DC: i32 = 20;
+
+/// Handles searching for hidden things, and turns found secret doors into real doors.
+pub struct PerceptionSystem {}
+
+impl<'a> System<'a> for PerceptionSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToSearch>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Hidden>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, SecretDoor>,
+        WriteStorage<'a, Door>,
+        WriteStorage<'a, Viewshed>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        ReadStorage<'a, EntryTrigger>,
+        WriteStorage<'a, Skills>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut map, player_entity, entities, mut wants_search, positions,
+            mut hidden, names, attributes, mut secret_doors, mut doors,
+            mut viewsheds, mut log, mut rng, mut particle_builder, traps, mut skills,
+        ) = data;
+
+        // Active searches
+        for (entity, _search, pos, attr) in (&entities, &wants_search, &positions, &attributes).join() {
+            if entity == *player_entity {
+                log.entries.push("You search your surroundings.".to_string());
+            }
+            // Only what the searcher can see is searched; walls holding secret doors are in view
+            let mut found: Vec<Entity> = Vec::new();
+            let visible = viewsheds.get(entity).map_or(&[][..], |v| &v.visible_tiles[..]);
+            for tile in visible.iter() {
+                if (tile.x - pos.x).abs() > SEARCH_RADIUS || (tile.y - pos.y).abs() > SEARCH_RADIUS { continue; }
+                let idx = map.xy_idx(tile.x, tile.y);
+                for other in map.tile_content[idx].iter() {
+                    let bonus = trap_spotting_bonus(traps.get(*other).is_some(), skills.get(entity));
+                    if hidden.get(*other).is_some() && perception_check(&mut rng, attr, skills.get(entity), SEARCH_DC - bonus) {
+                        found.push(*other);
+                    }
+                }
+            }
+            if !found.is_empty() && entity == *player_entity {
+                improve_skills(&[(entity, Skill::Perception)], &mut skills, *player_entity, &mut log);
+            }
+            for thing in found.iter() {
+                hidden.remove(*thing);
+                if entity == *player_entity {
+                    if let Some(name) = names.get(*thing) {
+                        log.entries.push(format!("You found a {}.", &name.name));
+                    }
+                }
+                if let Some(pos) = positions.get(*thing) {
+                    particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::CYAN), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('!'), 200.0);
+                }
+            }
+        }
+        wants_search.clear();
+
+        // Secret doors which are no longer hidden become ordinary doors
+        let mut revealed: Vec<Entity> = Vec::new();
+        for (entity, _secret, pos, _hidden) in (&entities, &secret_doors, &positions, !&hidden).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            map.tiles[idx] = TileType::Floor;
+            revealed.push(entity);
+        }
+        for door in revealed.iter() {
+            secret_doors.remove(*door);
+            if doors.get(*door).is_none() {
+                doors.insert(*door, Door{ open: false }).expect("Unable to insert door");
+            }
+        }
+        if !revealed.is_empty() {
+            for v in (&mut viewsheds).join() {
+                v.dirty = true;
+            }
+        }
+    }
+}
@@ -1,10 +1,15 @@
-use rltk::{VirtualKeyCode, Rltk, Point};
+use rltk::{VirtualKeyCode, Rltk, Point, RGB};
 use specs::prelude::*;
 use super::{
     Position, Player, State, Map, Viewshed, RunState, Pools,
-    WantsToMelee, Item, gamelog::GameLog, WantsToPickupItem, TileType, Monster,
+    WantsToMelee, Item, gamelog::GameLog, WantsToPickupItem, Monster,
     HungerClock, HungerState, EntityMoved, Door, BlocksVisibility, BlocksTile,
     Renderable, Bystander, Vendor, options::OPTIONS, options::KeybindType,
+    Hidden, WantsToSearch, Locked, Key, InBackpack, Name, map::tile_has_flag,
+    doors::set_door_open, camera, gui, EntryTrigger, WantsToDisarm, StatusEffects, StatusEffect,
+    status_effect_system::{impairment, Impairment, stagger_direction, struggle}, Equipped, RangedWeapon, Ammunition, EquipmentSlot, KnownSpells,
+    Encumbrance, EncumbranceState, stealth_system::{NoiseBuilder, FOOTSTEP_NOISE, DOOR_NOISE},
+    Faction, Grudges, Reaction, raws::{RAWS, reaction_to_player},
 };
 use std::cmp::{min, max};
 
@@ -18,9 +23,22 @@ enum Command {
     Remove,
     Menu,
     Descend,
+    Search,
+    CloseDoor,
+    DisarmTrap,
+    Fire,
+    Cast,
     Undefined,
 }
 
+impl Command {
+    /// Whether the command acts straight away, rather than opening a menu (or doing nothing).
+    fn takes_turn(&self) -> bool {
+        matches!(self, Command::Move{..} | Command::Wait | Command::Get | Command::Descend
+            | Command::Search | Command::CloseDoor | Command::DisarmTrap)
+    }
+}
+
 fn key_to_command(key: VirtualKeyCode) -> Command {
     let keybinds = OPTIONS.lock().unwrap().keybinds;
     match keybinds {
@@ -37,6 +55,11 @@ fn key_to_command(key: VirtualKeyCode) -> Command {
                 VirtualKeyCode::M => return Command::Move{x: 1, y: 1},
                 VirtualKeyCode::Semicolon => return Command::Wait,
                 VirtualKeyCode::D => return Command::Drop,
+                VirtualKeyCode::S => return Command::Search,
+                VirtualKeyCode::C => return Command::CloseDoor,
+                VirtualKeyCode::X => return Command::DisarmTrap,
+                VirtualKeyCode::F => return Command::Fire,
+                VirtualKeyCode::Z => return Command::Cast,
                 _ => {}
             }
         }
@@ -52,6 +75,11 @@ fn key_to_command(key: VirtualKeyCode) -> Command {
                 VirtualKeyCode::Numpad3 => return Command::Move{x: 1, y: 1},
                 VirtualKeyCode::Numpad5 => return Command::Wait,
                 VirtualKeyCode::D => return Command::Drop,
+                VirtualKeyCode::S => return Command::Search,
+                VirtualKeyCode::C => return Command::CloseDoor,
+                VirtualKeyCode::X => return Command::DisarmTrap,
+                VirtualKeyCode::F => return Command::Fire,
+                VirtualKeyCode::Z => return Command::Cast,
                 _ => {}
             }
         }
@@ -67,6 +95,11 @@ fn key_to_command(key: VirtualKeyCode) -> Command {
                 VirtualKeyCode::C => return Command::Move{x: 1, y: 1},
                 VirtualKeyCode::X => return Command::Wait,
                 VirtualKeyCode::T => return Command::Drop,
+                VirtualKeyCode::F => return Command::Search,
+                VirtualKeyCode::V => return Command::CloseDoor,
+                VirtualKeyCode::B => return Command::DisarmTrap,
+                VirtualKeyCode::Tab => return Command::Fire,
+                VirtualKeyCode::M => return Command::Cast,
                 _ => {}
             }
         }
@@ -84,6 +117,7 @@ fn key_to_command(key: VirtualKeyCode) -> Command {
 }
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    let (delta_x, delta_y) = stagger(delta_x, delta_y, ecs);
     let mut positions = ecs.write_storage::<Position>();
     let players = ecs.read_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
@@ -98,6 +132,16 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let mut renderables = ecs.write_storage::<Renderable>();
     let bystanders = ecs.read_storage::<Bystander>();
     let vendors = ecs.read_storage::<Vendor>();
+    let factions = ecs.read_storage::<Faction>();
+    let grudges = ecs.read_storage::<Grudges>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let mut locked = ecs.write_storage::<Locked>();
+    let keys = ecs.read_storage::<Key>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let names = ecs.read_storage::<Name>();
+    let mut status_effects = ecs.write_storage::<StatusEffects>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    let mut noise = ecs.fetch_mut::<NoiseBuilder>();
 
     let mut swap_entities: Vec<(Entity, i32, i32)> = Vec::new();
 
@@ -112,7 +156,13 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
         for potential_target in map.tile_content[destination_idx].iter() {
             let bystander = bystanders.get(*potential_target);
             let vendor = vendors.get(*potential_target);
-            if bystander.is_some() || vendor.is_some() {
+            // Townsfolk the player has turned against them won't step aside any more
+            let hostile = factions.get(*potential_target).is_some_and(|f| {
+                reaction_to_player(&f.name, grudges.get(entity), &RAWS.lock().unwrap()) != Reaction::Ignore
+            });
+            if (bystander.is_some() || vendor.is_some()) && !hostile {
+                if status_effects.get(entity).is_some_and(|s| s.has(StatusEffect::Entangled)) { continue; } // Can't swap places while caught in a net
+
                 // Note that we want to move the bystander
                 swap_entities.push((*potential_target, pos.x, pos.y));
 
@@ -128,30 +178,59 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             } else {
                 let target = combat_stats.get(*potential_target);
                 if let Some(_target) = target {
+                    if status_effects.get(entity).is_some_and(|s| s.has(StatusEffect::Fear)) {
+                        gamelog.entries.push("You are too frightened to attack!".to_string());
+                        return;
+                    }
                     wants_to_melee.insert(entity, WantsToMelee{ target: *potential_target }).expect("Add target failed.");
                     return; // Don't move after attacking.
                 }
             }
             let door = doors.get_mut(*potential_target);
-            if let Some(door) = door {
-                door.open = true;
-                blocks_visibility.remove(*potential_target);
-                blocks_movement.remove(*potential_target);
-                let glyph = renderables.get_mut(*potential_target).unwrap();
-                glyph.glyph = rltk::to_cp437('/');
-                viewshed.dirty = true;
-                opened_door = true;
+            if let (Some(door), None) = (door, hidden.get(*potential_target)) {
+                if let Some(lock) = locked.get(*potential_target) {
+                    let door_name = names.get(*potential_target).map_or("door".to_string(), |n| n.name.clone());
+                    let key = (&keys, &backpack, &names).join()
+                        .find(|(key, pack, _name)| pack.owner == entity && key.lock == lock.lock);
+                    match key {
+                        None => {
+                            gamelog.entries.push(format!("The {} is locked.", door_name));
+                            continue;
+                        }
+                        Some((_key, _pack, key_name)) => {
+                            gamelog.entries.push(format!("You unlock the {} with the {}.", door_name, key_name.name));
+                            locked.remove(*potential_target);
+                        }
+                    }
+                }
+                if !door.open {
+                    set_door_open(*potential_target, true, &mut doors, &mut blocks_visibility, &mut blocks_movement, &mut renderables);
+                    viewshed.dirty = true;
+                    opened_door = true;
+                    noise.make(pos.x, pos.y, DOOR_NOISE);
+                }
             }
         }
         if !map.blocked[destination_idx] {
+            if struggle(&mut status_effects, entity) {
+                gamelog.entries.push("You struggle against the net.".to_string());
+                continue;
+            }
             pos.x = min(map.width-1, max(0, pos.x + delta_x));
             pos.y = min(map.height-1, max(0, pos.y + delta_y));
+            let (slide_x, slide_y) = map.slide(pos.x, pos.y, delta_x, delta_y);
+            if slide_x != pos.x || slide_y != pos.y {
+                gamelog.entries.push("You slide across the ice.".to_string());
+                pos.x = slide_x;
+                pos.y = slide_y;
+            }
 
             viewshed.dirty = true;
             let mut ppos = ecs.write_resource::<Point>();
             ppos.x = pos.x;
             ppos.y = pos.y;
             entity_moved.insert(entity, EntityMoved{}).expect("Unable to insert marker");
+            noise.make(pos.x, pos.y, FOOTSTEP_NOISE);
         }
     }
 
@@ -171,6 +250,270 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     }
 }
 
+/// Finds the open doors next to the player, as (dx, dy) offsets.
+fn adjacent_open_doors(ecs: &World) -> Vec<(i32, i32)> {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let doors = ecs.read_storage::<Door>();
+
+    let mut result = Vec::new();
+    for dy in -1 ..= 1 {
+        for dx in -1 ..= 1 {
+            let (x, y) = (player_pos.x + dx, player_pos.y + dy);
+            if (dx == 0 && dy == 0) || x < 0 || x > map.width-1 || y < 0 || y > map.height-1 { continue; }
+            let idx = map.xy_idx(x, y);
+            if map.tile_content[idx].iter().any(|e| doors.get(*e).is_some_and(|door| door.open)) {
+                result.push((dx, dy));
+            }
+        }
+    }
+    result
+}
+
+/// Closes the door at (dx, dy) from the player, unless something is standing in the doorway.
+/// Returns true if the door was closed.
+fn try_close_door(delta_x: i32, delta_y: i32, ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let mut doors = ecs.write_storage::<Door>();
+    let mut blocks_visibility = ecs.write_storage::<BlocksVisibility>();
+    let mut blocks_movement = ecs.write_storage::<BlocksTile>();
+    let mut renderables = ecs.write_storage::<Renderable>();
+    let mut viewsheds = ecs.write_storage::<Viewshed>();
+    let names = ecs.read_storage::<Name>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let (x, y) = (player_pos.x + delta_x, player_pos.y + delta_y);
+    if x < 0 || x > map.width-1 || y < 0 || y > map.height-1 { return false; }
+    let idx = map.xy_idx(x, y);
+    let door_entity = match map.tile_content[idx].iter().find(|e| doors.get(**e).is_some_and(|door| door.open)) {
+        None => {
+            gamelog.entries.push("There is no open door there.".to_string());
+            return false;
+        }
+        Some(door) => *door,
+    };
+    if let Some(in_the_way) = map.tile_content[idx].iter().find(|e| **e != door_entity) {
+        let what = names.get(*in_the_way).map_or("Something".to_string(), |n| n.name.clone());
+        gamelog.entries.push(format!("{} is in the way.", what));
+        return false;
+    }
+
+    set_door_open(door_entity, false, &mut doors, &mut blocks_visibility, &mut blocks_movement, &mut renderables);
+    gamelog.entries.push("You close the door.".to_string());
+    ecs.fetch_mut::<NoiseBuilder>().make(player_pos.x, player_pos.y, DOOR_NOISE);
+    // Nobody can see through the door any more
+    for v in (&mut viewsheds).join() {
+        v.dirty = true;
+    }
+    true
+}
+
+/// Closes the door next to the player. If there's more than one, ask which.
+fn close_door(ecs: &mut World) -> RunState {
+    let open_doors = adjacent_open_doors(ecs);
+    match open_doors.len() {
+        0 => {
+            ecs.fetch_mut::<GameLog>().entries.push("There is no open door next to you.".to_string());
+            RunState::AwaitingInput
+        }
+        1 => {
+            let (dx, dy) = open_doors[0];
+            if try_close_door(dx, dy, ecs) { RunState::PlayerTurn } else { RunState::AwaitingInput }
+        }
+        _ => RunState::ShowCloseDoor,
+    }
+}
+
+/// Asks the player to pick one of the tiles next to them, highlighting the ones worth picking.
+fn choose_direction(gs: &mut State, ctx: &mut Rltk, prompt: &str, targets: &[(i32, i32)]) -> (gui::ItemMenuResult, Option<(i32, i32)>) {
+    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(&gs.ecs, ctx);
+    ctx.print_color(5, 0, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), format!("{} (direction, or Escape to cancel)", prompt));
+    {
+        let player_pos = gs.ecs.fetch::<Point>();
+        for (dx, dy) in targets.iter() {
+            ctx.set_bg(player_pos.x + dx - min_x, player_pos.y + dy - min_y, RGB::named(rltk::BLUE));
+        }
+    }
+
+    match ctx.key {
+        None => (gui::ItemMenuResult::NoResponse, None),
+        Some(VirtualKeyCode::Escape) => (gui::ItemMenuResult::Cancel, None),
+        Some(key) => match key_to_command(key) {
+            Command::Move{x, y} => (gui::ItemMenuResult::Selected, Some((x, y))),
+            _ => (gui::ItemMenuResult::NoResponse, None),
+        }
+    }
+}
+
+/// Asks the player which way the door they want to close is.
+pub fn close_door_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    let open_doors = adjacent_open_doors(&gs.ecs);
+    match choose_direction(gs, ctx, "Close which door?", &open_doors) {
+        (gui::ItemMenuResult::Cancel, _) => RunState::AwaitingInput,
+        (gui::ItemMenuResult::Selected, Some((dx, dy))) => {
+            if try_close_door(dx, dy, &mut gs.ecs) { RunState::PlayerTurn } else { RunState::AwaitingInput }
+        }
+        _ => RunState::ShowCloseDoor,
+    }
+}
+
+/// Finds the traps the player knows about next to them, as (dx, dy) offsets.
+fn adjacent_known_traps(ecs: &World) -> Vec<(i32, i32)> {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let triggers = ecs.read_storage::<EntryTrigger>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    let mut result = Vec::new();
+    for dy in -1 ..= 1 {
+        for dx in -1 ..= 1 {
+            let (x, y) = (player_pos.x + dx, player_pos.y + dy);
+            if (dx == 0 && dy == 0) || x < 0 || x > map.width-1 || y < 0 || y > map.height-1 { continue; }
+            let idx = map.xy_idx(x, y);
+            if map.tile_content[idx].iter().any(|e| triggers.get(*e).is_some() && hidden.get(*e).is_none()) {
+                result.push((dx, dy));
+            }
+        }
+    }
+    result
+}
+
+/// Has a go at disarming the trap at (dx, dy) from the player. Returns true if there was a trap
+/// there to try.
+fn try_disarm_trap(delta_x: i32, delta_y: i32, ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let player_entity = ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+    let triggers = ecs.read_storage::<EntryTrigger>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let mut wants_disarm = ecs.write_storage::<WantsToDisarm>();
+
+    let (x, y) = (player_pos.x + delta_x, player_pos.y + delta_y);
+    if x < 0 || x > map.width-1 || y < 0 || y > map.height-1 { return false; }
+    let idx = map.xy_idx(x, y);
+    match map.tile_content[idx].iter().find(|e| triggers.get(**e).is_some() && hidden.get(**e).is_none()) {
+        None => {
+            ecs.fetch_mut::<GameLog>().entries.push("You don't know of a trap there.".to_string());
+            false
+        }
+        Some(trap) => {
+            wants_disarm.insert(*player_entity, WantsToDisarm{ trap: *trap }).expect("Unable to insert intent");
+            true
+        }
+    }
+}
+
+/// Tries to disarm a trap next to the player. If there's more than one, ask which.
+fn disarm_trap(ecs: &mut World) -> RunState {
+    let traps = adjacent_known_traps(ecs);
+    match traps.len() {
+        0 => {
+            ecs.fetch_mut::<GameLog>().entries.push("You don't know of any traps next to you.".to_string());
+            RunState::AwaitingInput
+        }
+        1 => {
+            let (dx, dy) = traps[0];
+            if try_disarm_trap(dx, dy, ecs) { RunState::PlayerTurn } else { RunState::AwaitingInput }
+        }
+        _ => RunState::ShowDisarmTrap,
+    }
+}
+
+/// Asks the player which way the trap they want to disarm is.
+pub fn disarm_trap_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    let traps = adjacent_known_traps(&gs.ecs);
+    match choose_direction(gs, ctx, "Disarm which trap?", &traps) {
+        (gui::ItemMenuResult::Cancel, _) => RunState::AwaitingInput,
+        (gui::ItemMenuResult::Selected, Some((dx, dy))) => {
+            if try_disarm_trap(dx, dy, &mut gs.ecs) { RunState::PlayerTurn } else { RunState::AwaitingInput }
+        }
+        _ => RunState::ShowDisarmTrap,
+    }
+}
+
+/// Starts aiming whatever ranged weapon the player is wielding, if they have anything to fire.
+fn fire(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let names = ecs.read_storage::<Name>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let weapon = (&equipped, &ranged_weapons).join()
+        .find(|(wielded, _ranged)| wielded.owner == *player_entity && wielded.slot == EquipmentSlot::Melee)
+        .map(|(_wielded, ranged)| ranged);
+    let weapon = match weapon {
+        None => {
+            gamelog.entries.push("You have nothing to fire.".to_string());
+            return RunState::AwaitingInput;
+        }
+        Some(weapon) => weapon,
+    };
+
+    if let Some(kind) = &weapon.ammo {
+        let has_ammo = (&ammunition, &backpack).join()
+            .any(|(ammo, pack)| pack.owner == *player_entity && ammo.kind == *kind && ammo.count > 0);
+        if !has_ammo {
+            let ammo_name = (&ammunition, &names).join()
+                .find(|(ammo, _name)| ammo.kind == *kind)
+                .map_or(format!("{}s", kind.to_lowercase()), |(_ammo, name)| name.name.to_lowercase());
+            gamelog.entries.push(format!("You are out of {}.", ammo_name));
+            return RunState::AwaitingInput;
+        }
+    }
+
+    RunState::ShowFiring{ range: weapon.range }
+}
+
+/// Starts casting a spell, if the player knows any.
+fn cast(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let known_spells = ecs.read_storage::<KnownSpells>();
+    if known_spells.get(*player_entity).is_none_or(|known| known.spells.is_empty()) {
+        ecs.fetch_mut::<GameLog>().entries.push("You don't know any spells.".to_string());
+        return RunState::AwaitingInput;
+    }
+    RunState::ShowSpells
+}
+
+/// A confused player doesn't always go where they meant to.
+fn stagger(delta_x: i32, delta_y: i32, ecs: &mut World) -> (i32, i32) {
+    let player_entity = ecs.fetch::<Entity>();
+    let status_effects = ecs.read_storage::<StatusEffects>();
+    let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+    if impairment(status_effects.get(*player_entity), &mut rng) == Impairment::Staggers {
+        ecs.fetch_mut::<GameLog>().entries.push("You stagger about in confusion.".to_string());
+        return stagger_direction(&mut rng);
+    }
+    (delta_x, delta_y)
+}
+
+/// Stunned (or slowed) players lose their turn, whatever they were trying to do, and overloaded
+/// ones sometimes do too. Only called for things which take a turn, so looking through the menus
+/// is always free. Returns true if they've lost this one.
+pub fn loses_turn(ecs: &World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let status_effects = ecs.read_storage::<StatusEffects>();
+    let status = status_effects.get(*player_entity);
+    let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+    let encumbrance = ecs.read_storage::<Encumbrance>();
+    if encumbrance.get(*player_entity).is_some_and(|b| b.state == EncumbranceState::Overloaded) && rng.roll_dice(1, 3) == 1 {
+        ecs.fetch_mut::<GameLog>().entries.push("You struggle under the weight of your pack.".to_string());
+        return true;
+    }
+    if impairment(status, &mut rng) != Impairment::LosesTurn { return false; }
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    if status.is_some_and(|s| s.has(StatusEffect::Stun)) {
+        gamelog.entries.push("You are stunned, and can't do a thing!".to_string());
+    } else {
+        gamelog.entries.push("You are moving too slowly to act.".to_string());
+    }
+    true
+}
+
 fn get_item(ecs: &mut World) {
     let player_pos = ecs.fetch::<Point>();
     let player_entity = ecs.fetch::<Entity>();
@@ -200,7 +543,7 @@ pub fn try_next_level(ecs: &mut World) -> bool {
     let player_pos = ecs.fetch::<Point>();
     let map = ecs.fetch::<Map>();
     let player_idx = map.xy_idx(player_pos.x, player_pos.y);
-    if map.tiles[player_idx] == TileType::DownStairs {
+    if tile_has_flag(map.tiles[player_idx], "stairs_down") {
         true
     } else {
         let mut gamelog = ecs.fetch_mut::<GameLog>();
@@ -209,6 +552,14 @@ pub fn try_next_level(ecs: &mut World) -> bool {
     }
 }
 
+/// Spends a turn searching the area around the player for hidden things.
+fn search(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut wants_search = ecs.write_storage::<WantsToSearch>();
+    wants_search.insert(*player_entity, WantsToSearch{}).expect("Unable to insert intent");
+    RunState::PlayerTurn
+}
+
 fn skip_turn(ecs: &mut World) -> RunState {
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
@@ -267,6 +618,7 @@ fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
         if let Some(ranged) = gs.ecs.read_storage::<Ranged>().get(carried_consumables[key as usize]) {
             return RunState::ShowTargeting{ range: ranged.range, item: carried_consumables[key as usize] };
         }
+        if loses_turn(&gs.ecs) { return RunState::PlayerTurn; }
         let mut intent = gs.ecs.write_storage::<WantsToUseItem>();
         intent.insert(
             *player_entity,
@@ -307,10 +659,28 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
         None => { return RunState::AwaitingInput } // Nothing happened
         Some(key) => {
             let command = key_to_command(key);
+            if command.takes_turn() && loses_turn(&gs.ecs) {
+                return RunState::PlayerTurn;
+            }
             match command {
                 // Wait button
                 Command::Wait => return skip_turn(&mut gs.ecs),
 
+                // Search for hidden things
+                Command::Search => return search(&mut gs.ecs),
+
+                // Close a door
+                Command::CloseDoor => return close_door(&mut gs.ecs),
+
+                // Disarm a trap
+                Command::DisarmTrap => return disarm_trap(&mut gs.ecs),
+
+                // Fire a ranged weapon
+                Command::Fire => return fire(&mut gs.ecs),
+
+                // Cast a spell
+                Command::Cast => return cast(&mut gs.ecs),
+
                 // Collect item
                 Command::Get => get_item(&mut gs.ecs),
 
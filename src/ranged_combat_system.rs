@@ -0,0 +1,202 @@
+use specs::prelude::*;
+use super::{
+    Attributes, WantsToShoot, Name, SufferDamage, gamelog::GameLog,
+    HungerClock, HungerState, particle_system::ParticleBuilder, Position,
+    Skills, Pools, Skill, Equipped, RangedWeapon, WeaponAttribute, EquipmentSlot,
+    Wearable, NaturalAttackDefense, Ammunition, InBackpack, melee_combat_system::{armour_class, shield_block}, improve_skills, Shield,
+    Encumbrance, Awareness, Alertness, stealth_system::{NoiseBuilder, RANGED_NOISE},
+};
+use crate::{skill_bonus};
+use rltk::Point;
+
+/// Finds the ammunition a ranged weapon needs in its wielder's pack, if they have any left.
+pub fn find_ammunition(
+    owner: Entity,
+    weapon: &RangedWeapon,
+    entities: &Entities,
+    ammunition: &WriteStorage<Ammunition>,
+    backpack: &ReadStorage<InBackpack>,
+) -> Option<Entity> {
+    let kind = weapon.ammo.as_ref()?;
+    (entities, ammunition, backpack).join()
+        .find(|(_entity, ammo, pack)| pack.owner == owner && ammo.kind == *kind && ammo.count > 0)
+        .map(|(entity, _ammo, _pack)| entity)
+}
+
+/// Resolves attacks made with bows, slings, thrown weapons and the like. It works just like
+/// melee, except that the wielder's ranged skill counts, and every shot uses up ammunition.
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Skills>,
+        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, Pools>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        WriteStorage<'a, Equipped>,
+        ReadStorage<'a, RangedWeapon>,
+        ReadStorage<'a, Wearable>,
+        ReadStorage<'a, NaturalAttackDefense>,
+        WriteStorage<'a, Ammunition>,
+        ReadStorage<'a, InBackpack>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Encumbrance>,
+        WriteStorage<'a, Awareness>,
+        WriteExpect<'a, NoiseBuilder>,
+        ReadStorage<'a, Shield>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities, mut log, mut wants_shoot, names, attributes, mut skills,
+            mut inflict_damage, mut particle_builder, mut positions, hunger_clock,
+            pools, mut rng, mut equipped_items, ranged_weapons, wearables, natural,
+            mut ammunition, backpack, player_entity, encumbrance, mut awareness, mut noise, shields,
+        ) = data;
+
+        let mut thrown: Vec<(Entity, Point)> = Vec::new();
+        // Skills which got some practice this turn
+        let mut practised: Vec<(Entity, Skill)> = Vec::new();
+
+        for (entity, wants_shoot, name, attacker_attributes, attacker_skills, attacker_pools) in (&entities, &wants_shoot, &names, &attributes, &skills, &pools).join() {
+            // Are the attacker and defender both alive? Only attack if they are
+            let target_pools = pools.get(wants_shoot.target).unwrap();
+            let target_attributes = attributes.get(wants_shoot.target).unwrap();
+            let target_skills = skills.get(wants_shoot.target).unwrap();
+            if attacker_pools.hit_points.current <= 0 || target_pools.hit_points.current <= 0 { continue; }
+            let target_name = names.get(wants_shoot.target).unwrap();
+
+            let weapon = (&entities, &equipped_items, &ranged_weapons).join()
+                .find(|(_item, wielded, _ranged)| wielded.owner == entity && wielded.slot == EquipmentSlot::Melee)
+                .map(|(item, _wielded, ranged)| (item, ranged.clone()));
+            let (weapon_entity, weapon_info) = match weapon {
+                None => continue,
+                Some(weapon) => weapon,
+            };
+
+            // Use up a round of ammunition. Monsters are assumed to have brought enough.
+            if weapon_info.ammo.is_some() && entity == *player_entity {
+                if let Some(ammo_entity) = find_ammunition(entity, &weapon_info, &entities, &ammunition, &backpack) {
+                    let ammo = ammunition.get_mut(ammo_entity).unwrap();
+                    ammo.count -= 1;
+                    if ammo.count < 1 {
+                        if let Some(ammo_name) = names.get(ammo_entity) {
+                            log.entries.push(format!("{} uses the last of the {}.", name.name, ammo_name.name));
+                        }
+                        entities.delete(ammo_entity).expect("Unable to delete");
+                    }
+                } else {
+                    log.entries.push("You have nothing left to fire!".to_string());
+                    continue;
+                }
+            }
+
+            // Show the shot flying to its target
+            let (attacker_pos, target_pos) = match (positions.get(entity), positions.get(wants_shoot.target)) {
+                (Some(a), Some(t)) => (Point::new(a.x, a.y), Point::new(t.x, t.y)),
+                _ => continue,
+            };
+            for step in rltk::line2d(rltk::LineAlg::Bresenham, attacker_pos, target_pos).iter().skip(1) {
+                particle_builder.request(step.x, step.y, rltk::RGB::named(rltk::CYAN), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('*'), 100.0);
+            }
+            if weapon_info.thrown {
+                thrown.push((weapon_entity, target_pos));
+            }
+
+            // Calculate attack roll
+            let natural_roll = rng.roll_dice(1, 20);
+            let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might
+                { attacker_attributes.might.bonus }
+                else { attacker_attributes.quickness.bonus };
+            let skill_hit_bonus = skill_bonus(Skill::Ranged, attacker_skills);
+            let weapon_hit_bonus = weapon_info.hit_bonus;
+            let mut status_hit_bonus = 0;
+            if let Some(hc) = hunger_clock.get(entity) { // Well Fed grants +1
+                if hc.state == HungerState::WellFed {
+                    status_hit_bonus += 1;
+                }
+            }
+            if let Some(burden) = encumbrance.get(entity) { // Struggling under a heavy load
+                status_hit_bonus -= burden.state.hit_penalty();
+            }
+            let modified_hit_roll = natural_roll + attribute_hit_bonus + skill_hit_bonus + status_hit_bonus + weapon_hit_bonus;
+
+            // The player shooting makes some noise, and being shot at puts anyone on their guard
+            if let (true, Some(pos)) = (entity == *player_entity, positions.get(entity)) {
+                noise.make(pos.x, pos.y, RANGED_NOISE);
+            }
+            if let Some(target_awareness) = awareness.get_mut(wants_shoot.target) {
+                target_awareness.state = Alertness::Alert;
+            }
+
+            // Calculate defender's AC
+            let (armour_class, armour_item_bonus) = armour_class(
+                wants_shoot.target, &equipped_items, &wearables, &natural, target_attributes, target_skills
+            );
+
+            // Determine if the attack hits
+            if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll >= armour_class) {
+                let mut base_damage = rng.roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
+                if natural_roll == 20 {
+                    log.entries.push("Critical hit!!".to_string());
+                    base_damage += rng.roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
+                }
+                // Only thrown weapons get any help from the thrower's strength
+                let attr_damage_bonus = if weapon_info.thrown { attacker_attributes.might.bonus } else { 0 };
+                let skill_damage_bonus = skill_bonus(Skill::Ranged, attacker_skills);
+                let weapon_damage_bonus = weapon_info.damage_bonus;
+
+                let mut damage = i32::max(0, base_damage + attr_damage_bonus + skill_damage_bonus + weapon_damage_bonus);
+
+                // A shield can still catch the shot, unless it was a critical
+                if natural_roll != 20 {
+                    if let Some((shield_name, block_amount)) = shield_block(
+                        wants_shoot.target, &equipped_items, &shields, &names, target_attributes, target_skills, &mut rng
+                    ) {
+                        practised.push((wants_shoot.target, Skill::Defense));
+                        damage = i32::max(0, damage - block_amount);
+                        if damage == 0 {
+                            log.entries.push(format!("{} shoots at {}, but the {} stops it!", name.name, target_name.name, shield_name));
+                            particle_builder.request(target_pos.x, target_pos.y, rltk::RGB::named(rltk::CYAN), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
+                            continue;
+                        }
+                        log.entries.push(format!("{} partly blocks the shot with the {}.", target_name.name, shield_name));
+                    }
+                }
+
+                SufferDamage::new_damage(&mut inflict_damage, wants_shoot.target, damage, weapon_info.damage_type, entity == *player_entity);
+                practised.push((entity, Skill::Ranged));
+                log.entries.push(format!("{} shoots {} for {} damage!", name.name, target_name.name, damage));
+                particle_builder.request(target_pos.x, target_pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
+            } else if natural_roll == 1 {
+                // Critical miss!
+                log.entries.push(format!("{} shoots at {} - critical miss!", name.name, target_name.name));
+            } else if modified_hit_roll > armour_class - armour_item_bonus {
+                // The shot wouldn't have hit even without armour, so it's a dodge
+                practised.push((wants_shoot.target, Skill::Defense));
+                log.entries.push(format!("{} shoots at {}, but {} dodges!", name.name, target_name.name, target_name.name));
+            } else {
+                log.entries.push(format!("{} shoots at {}, but it glances off the armour!", name.name, target_name.name));
+                practised.push((wants_shoot.target, Skill::Defense));
+            }
+        }
+
+        // Thrown weapons end up wherever they were thrown
+        for (weapon, landed) in thrown.iter() {
+            equipped_items.remove(*weapon);
+            positions.insert(*weapon, Position{ x: landed.x, y: landed.y }).expect("Unable to insert position");
+        }
+
+        wants_shoot.clear();
+        improve_skills(&practised, &mut skills, *player_entity, &mut log);
+    }
+}
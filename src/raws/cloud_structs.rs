@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct Cloud {
+    pub name: String,
+    pub color: String,
+    pub opacity: f32,
+    pub density: i32,
+    pub decay: i32,
+    pub spreads: bool,
+    pub blocks_visibility: Option<bool>,
+    pub damage: Option<String>,
+    pub damage_type: Option<String>,
+    pub healing: Option<i32>,
+    pub confusion: Option<i32>,
+}
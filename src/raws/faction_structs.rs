@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct FactionInfo {
+    pub name: String,
+    pub responses: HashMap<String, String>,
+    pub angered: Option<String>,
+    pub protects: Option<Vec<String>>,
+}
@@ -4,10 +4,14 @@ use std::collections::HashMap;
 #[derive(Deserialize, Debug)]
 pub struct Item {
     pub name: String,
+    pub weight: Option<f32>,
     pub renderable: Option<Renderable>,
     pub consumable: Option<Consumable>,
     pub weapon: Option<Weapon>,
     pub wearable: Option<Wearable>,
+    pub shield: Option<Shield>,
+    pub key: Option<String>,
+    pub ammunition: Option<Ammunition>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -18,6 +22,12 @@ pub struct Renderable {
     pub order: i32,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct LightSource {
+    pub range: i32,
+    pub color: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Consumable {
     pub effects: HashMap<String, String>
@@ -29,10 +39,34 @@ pub struct Weapon {
     pub attribute: String,
     pub base_damage: String,
     pub hit_bonus: i32,
+    pub damage_type: Option<String>,
+    pub ammo: Option<String>,
+    pub thrown: Option<bool>,
+    pub two_handed: Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Ammunition {
+    pub kind: String,
+    pub count: i32,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Wearable {
     pub armour_class: f32,
-    pub slot: String
+    pub slot: String,
+    pub resistances: Option<Resistances>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Shield {
+    pub block_chance: i32,
+    pub block_amount: i32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Resistances {
+    pub resist: Option<Vec<String>>,
+    pub immune: Option<Vec<String>>,
+    pub weak: Option<Vec<String>>,
 }
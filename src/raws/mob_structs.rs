@@ -1,5 +1,5 @@
 use serde::{Deserialize};
-use super::{Renderable};
+use super::{Renderable, LightSource, Resistances};
 use std::collections::HashMap;
 
 #[derive(Deserialize, Debug)]
@@ -19,6 +19,12 @@ pub struct Mob {
     pub equipped: Option<Vec<String>>,
     pub natural: Option<MobNatural>,
     pub loot_table: Option<String>,
+    pub light: Option<LightSource>,
+    pub resistances: Option<Resistances>,
+    pub abilities: Option<Vec<MobAbility>>,
+    pub morale: Option<MobMorale>,
+    pub can_sleep: Option<bool>,
+    pub faction: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -29,6 +35,12 @@ pub struct MobAttributes {
     pub intellence: Option<i32>
 }
 
+#[derive(Deserialize, Debug)]
+pub struct MobMorale {
+    pub value: i32,
+    pub on_break: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct MobMemory {
     pub max_memory: i32,
@@ -44,5 +56,18 @@ pub struct MobNatural {
 pub struct NaturalAttack {
     pub name: String,
     pub hit_bonus: i32,
-    pub damage: String
+    pub damage: String,
+    pub damage_type: Option<String>,
+    pub status: Option<HashMap<String, i32>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MobAbility {
+    pub spell: String,
+    pub chance: f32,
+    pub range: f32,
+    pub min_range: Option<f32>,
+    pub cooldown: Option<i32>,
+    pub self_cast: Option<bool>,
+    pub when_hurt: Option<bool>,
 }
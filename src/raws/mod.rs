@@ -12,6 +12,16 @@ mod rawmaster;
 pub use rawmaster::*;
 mod loot_structs;
 use loot_structs::*;
+mod room_theme_structs;
+use room_theme_structs::*;
+mod tile_structs;
+use tile_structs::*;
+mod cloud_structs;
+use cloud_structs::*;
+mod spell_structs;
+use spell_structs::*;
+mod faction_structs;
+use faction_structs::*;
 
 use std::sync::Mutex;
 use serde::{Deserialize};
@@ -27,6 +37,12 @@ pub struct Raws {
     pub props: Vec<Prop>,
     pub spawn_table: Vec<SpawnTableEntry>,
     pub loot_tables: Vec<LootTable>,
+    pub room_themes: Vec<RoomTheme>,
+    pub tiles: Vec<Tile>,
+    pub tile_themes: Vec<TileTheme>,
+    pub clouds: Vec<Cloud>,
+    pub spells: Vec<Spell>,
+    pub faction_table: Vec<FactionInfo>,
 }
 
 pub fn load_raws() {
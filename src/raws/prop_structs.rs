@@ -1,5 +1,5 @@
 use serde::{Deserialize};
-use super::{Renderable};
+use super::{Renderable, LightSource};
 use std::collections::HashMap;
 
 #[derive(Deserialize, Debug)]
@@ -10,10 +10,14 @@ pub struct Prop {
     pub blocks_tile: Option<bool>,
     pub blocks_visibility: Option<bool>,
     pub door_open: Option<bool>,
+    pub secret_door: Option<bool>,
+    pub lock: Option<String>,
+    pub light: Option<LightSource>,
     pub entry_trigger: Option<EntryTrigger>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct EntryTrigger {
-    pub effects: HashMap<String, String>
+    pub effects: HashMap<String, String>,
+    pub disarm_dc: Option<i32>,
 }
@@ -5,6 +5,7 @@ use crate::{attr_bonus, npc_hp, mana_at_level, parse_dice_string};
 use super::{Raws};
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use crate::random_table::{RandomTable};
+use crate::map::{TileRegistry, TileInfo, TileLook, CloudInfo};
 
 /// How to choose where to spawn an entity
 /// * `AtPosition{x, y}` - Spawns the entity at tile (x, y)
@@ -14,12 +15,22 @@ pub enum SpawnType {
     Carried { by: Entity }
 }
 
+/// How one faction feels about the others, parsed from the raws.
+struct FactionReactions {
+    responses: HashMap<String, Reaction>,
+    angered: Option<Reaction>,
+    protects: Vec<String>,
+}
+
 pub struct RawMaster {
     raws: Raws,
     item_index: HashMap<String, usize>,
     mob_index: HashMap<String, usize>,
     prop_index: HashMap<String, usize>,
     loot_index: HashMap<String, usize>,
+    room_theme_index: HashMap<String, usize>,
+    spell_index: HashMap<String, usize>,
+    faction_index: HashMap<String, FactionReactions>,
 }
 
 impl RawMaster {
@@ -31,12 +42,21 @@ impl RawMaster {
                 mobs: Vec::new(),
                 props: Vec::new(),
                 spawn_table: Vec::new(),
-                loot_tables: Vec::new()
+                loot_tables: Vec::new(),
+                room_themes: Vec::new(),
+                tiles: Vec::new(),
+                tile_themes: Vec::new(),
+                clouds: Vec::new(),
+                spells: Vec::new(),
+                faction_table: Vec::new(),
             },
             item_index: HashMap::new(),
             mob_index: HashMap::new(),
             prop_index: HashMap::new(),
             loot_index: HashMap::new(),
+            room_theme_index: HashMap::new(),
+            spell_index: HashMap::new(),
+            faction_index: HashMap::new(),
         }
     }
 
@@ -70,14 +90,123 @@ impl RawMaster {
         for (i, loot) in self.raws.loot_tables.iter().enumerate() {
             self.loot_index.insert(loot.name.clone(), i);
         }
+        self.spell_index = HashMap::new();
+        for (i, spell) in self.raws.spells.iter().enumerate() {
+            if self.spell_index.contains_key(&spell.name) {
+                rltk::console::log(format!("WARNING - duplicate spell name in raws [{}]", spell.name));
+            }
+            self.spell_index.insert(spell.name.clone(), i);
+        }
+
+        self.faction_index = HashMap::new();
+        for faction in self.raws.faction_table.iter() {
+            let responses = faction.responses.iter()
+                .map(|(other, response)| (other.clone(), string_to_reaction(response)))
+                .collect();
+            self.faction_index.insert(faction.name.clone(), FactionReactions{
+                responses,
+                angered: faction.angered.as_ref().map(|a| string_to_reaction(a)),
+                protects: faction.protects.clone().unwrap_or_default(),
+            });
+        }
+        for mob in self.raws.mobs.iter() {
+            if let Some(faction) = &mob.faction {
+                if !self.faction_index.contains_key(faction) {
+                    rltk::console::log(format!("WARNING - {} belongs to unknown faction {}", mob.name, faction));
+                }
+            }
+        }
 
         for spawn in self.raws.spawn_table.iter() {
             if !used_names.contains(&spawn.name) {
                 rltk::console::log(format!("WARNING - Spawn tables reference unspecified entity {}", spawn.name));
             }
         }
+
+        for (i, theme) in self.raws.room_themes.iter().enumerate() {
+            self.room_theme_index.insert(theme.name.clone(), i);
+            let contents = [&theme.furniture, &theme.mobs, &theme.loot];
+            for content in contents.iter().filter_map(|c| c.as_ref()).flatten() {
+                if !used_names.contains(&content.name) {
+                    rltk::console::log(format!("WARNING - Room theme {} references unspecified entity {}", theme.name, content.name));
+                }
+            }
+        }
+
+        crate::map::register_tiles(self.build_tile_registry());
+        crate::map::register_clouds(self.build_cloud_registry());
     }
 
+    /// Turns the raw cloud definitions into a registry the map can use.
+    fn build_cloud_registry(&self) -> HashMap<String, CloudInfo> {
+        let mut registry = HashMap::new();
+        for cloud in self.raws.clouds.iter() {
+            if registry.contains_key(&cloud.name) {
+                rltk::console::log(format!("WARNING - duplicate cloud in raws [{}]", cloud.name));
+            }
+            registry.insert(cloud.name.clone(), CloudInfo{
+                color: rltk::RGB::from_hex(&cloud.color).expect("Invalid RGB"),
+                opacity: cloud.opacity,
+                density: cloud.density,
+                decay: cloud.decay,
+                spreads: cloud.spreads,
+                blocks_visibility: cloud.blocks_visibility.unwrap_or(false),
+                damage: cloud.damage.clone(),
+                damage_type: cloud.damage_type.as_ref().map_or(DamageType::Untyped, |t| string_to_damage_type(t)),
+                healing: cloud.healing.unwrap_or(0),
+                confusion: cloud.confusion.unwrap_or(0),
+            });
+        }
+        registry
+    }
+
+    /// Turns the raw tile definitions and themes into a registry the map can use.
+    fn build_tile_registry(&self) -> TileRegistry {
+        let mut registry = TileRegistry::default();
+        for tile in self.raws.tiles.iter() {
+            let id = registry.tile_id(&tile.name);
+            if registry.get(id).is_some() {
+                rltk::console::log(format!("WARNING - duplicate tile in raws [{}]", tile.name));
+            }
+            let flags: HashSet<String> = tile.flags.iter().flatten().cloned().collect();
+            registry.insert(id, TileInfo{
+                walkable: tile.walkable,
+                opaque: tile.opaque,
+                cost: tile.cost.unwrap_or(1.0),
+                look: TileLook{
+                    glyph: rltk::to_cp437(tile.glyph.chars().next().unwrap()),
+                    fg: rltk::RGB::from_hex(&tile.fg).expect("Invalid RGB"),
+                    bg: rltk::RGB::from_hex(&tile.bg).expect("Invalid RGB"),
+                    connect_walls: tile.connect_walls.unwrap_or(false),
+                },
+                hazardous: flags.contains("hazard"),
+                flags,
+            });
+        }
+
+        for theme in self.raws.tile_themes.iter() {
+            let mut looks = HashMap::new();
+            for entry in theme.tiles.iter() {
+                let defined = registry.id_of(&entry.name).and_then(|id| registry.get(id).map(|info| (id, info.look)));
+                let (id, base) = match defined {
+                    None => {
+                        rltk::console::log(format!("WARNING - Tile theme {} references undefined tile {}", theme.name, entry.name));
+                        continue;
+                    }
+                    Some(defined) => defined,
+                };
+                looks.insert(id, TileLook{
+                    glyph: entry.glyph.as_ref().map_or(base.glyph, |g| rltk::to_cp437(g.chars().next().unwrap())),
+                    fg: entry.fg.as_ref().map_or(base.fg, |c| rltk::RGB::from_hex(c).expect("Invalid RGB")),
+                    bg: entry.bg.as_ref().map_or(base.bg, |c| rltk::RGB::from_hex(c).expect("Invalid RGB")),
+                    connect_walls: entry.connect_walls.unwrap_or(base.connect_walls),
+                });
+            }
+            registry.add_theme(&theme.name, looks);
+        }
+
+        registry
+    }
 }
 
 fn find_slot_for_equippable_item(tag: &str, raws: &RawMaster) -> EquipmentSlot {
@@ -119,6 +248,13 @@ fn get_renderable_component(renderable: &super::item_structs::Renderable) -> cra
     }
 }
 
+fn get_light_component(light: &super::item_structs::LightSource) -> crate::components::LightSource {
+    crate::components::LightSource {
+        color: rltk::RGB::from_hex(&light.color).expect("Invalid RGB"),
+        range: light.range,
+    }
+}
+
 pub fn string_to_slot(slot: &str) -> EquipmentSlot {
     match slot {
         "Shield" => EquipmentSlot::Shield,
@@ -132,6 +268,155 @@ pub fn string_to_slot(slot: &str) -> EquipmentSlot {
     }
 }
 
+pub fn string_to_reaction(reaction: &str) -> Reaction {
+    match reaction {
+        "attack" => Reaction::Attack,
+        "flee" => Reaction::Flee,
+        "follow" => Reaction::Follow,
+        "ignore" => Reaction::Ignore,
+        _ => { rltk::console::log(format!("Warning: unknown faction reaction [{}]", reaction)); Reaction::Ignore }
+    }
+}
+
+pub fn string_to_morale_break(on_break: &str) -> MoraleBreak {
+    match on_break {
+        "flee" => MoraleBreak::Flee,
+        "surrender" => MoraleBreak::Surrender,
+        "call_for_help" => MoraleBreak::CallForHelp,
+        _ => { rltk::console::log(format!("Warning: unknown morale break [{}]", on_break)); MoraleBreak::Flee }
+    }
+}
+
+/// How a member of `my_faction` reacts to a member of `their_faction`. Factions fall back on their
+/// "Default" response to anyone they don't list, and unknown factions ignore everyone.
+pub fn faction_reaction(my_faction: &str, their_faction: &str, raws: &RawMaster) -> Reaction {
+    match raws.faction_index.get(my_faction) {
+        None => Reaction::Ignore,
+        Some(faction) => faction.responses.get(their_faction)
+            .or_else(|| faction.responses.get("Default"))
+            .copied()
+            .unwrap_or(Reaction::Ignore),
+    }
+}
+
+/// How a member of `my_faction` reacts to the player, who may have angered them (or someone they
+/// look out for).
+pub fn reaction_to_player(my_faction: &str, grudges: Option<&Grudges>, raws: &RawMaster) -> Reaction {
+    if let (Some(faction), Some(grudges)) = (raws.faction_index.get(my_faction), grudges) {
+        if let Some(angered) = faction.angered {
+            if grudges.factions.contains(my_faction) || faction.protects.iter().any(|p| grudges.factions.contains(p)) {
+                return angered;
+            }
+        }
+    }
+    faction_reaction(my_faction, "Player", raws)
+}
+
+/// True if the faction takes it badly when the player attacks one of them.
+pub fn faction_holds_grudges(faction: &str, raws: &RawMaster) -> bool {
+    raws.faction_index.get(faction).is_some_and(|f| f.angered.is_some())
+}
+
+/// Converts a damage type named in the raws. Anything the raws don't give a damage type deals
+/// Untyped damage, which nothing resists.
+pub fn string_to_damage_type(damage_type: &str) -> DamageType {
+    match damage_type {
+        "Slashing" => DamageType::Slashing,
+        "Piercing" => DamageType::Piercing,
+        "Bludgeoning" => DamageType::Bludgeoning,
+        "Fire" => DamageType::Fire,
+        "Cold" => DamageType::Cold,
+        "Poison" => DamageType::Poison,
+        "Acid" => DamageType::Acid,
+        _ => { rltk::console::log(format!("Warning: unknown damage type [{}]", damage_type)); DamageType::Untyped }
+    }
+}
+
+/// Looks up the status effect a raws effect name refers to, if it is one.
+fn string_to_status_effect(effect: &str) -> Option<StatusEffect> {
+    match effect {
+        "stunned" => Some(StatusEffect::Stun),
+        "confusion" => Some(StatusEffect::Confusion),
+        "poison" => Some(StatusEffect::Poison),
+        "regeneration" => Some(StatusEffect::Regeneration),
+        "haste" => Some(StatusEffect::Haste),
+        "slow" => Some(StatusEffect::Slow),
+        "blindness" => Some(StatusEffect::Blindness),
+        "fear" => Some(StatusEffect::Fear),
+        _ => None
+    }
+}
+
+fn get_resistances_component(resistances: &super::item_structs::Resistances) -> Resistances {
+    let to_types = |names: &Option<Vec<String>>| -> Vec<DamageType> {
+        names.as_ref().map_or(Vec::new(), |names| names.iter().map(|n| string_to_damage_type(n)).collect())
+    };
+    Resistances{
+        resist: to_types(&resistances.resist),
+        immune: to_types(&resistances.immune),
+        weak: to_types(&resistances.weak),
+    }
+}
+
+/// Adds the components for what a consumable or spell does when it's used. `kind` is only used
+/// to warn about effects which don't exist.
+fn add_effect_components<'a>(mut eb: EntityBuilder<'a>, effects: &HashMap<String, String>, kind: &str) -> EntityBuilder<'a> {
+    let mut statuses: Vec<(StatusEffect, i32)> = Vec::new();
+    for effect in effects.iter() {
+        let effect_name = effect.0.as_str();
+        match effect_name {
+            "provides_healing" => {
+                eb = eb.with(ProvidesHealing{ heal_amount: effect.1.parse::<i32>().unwrap() });
+            },
+            "ranged" => { eb = eb.with(Ranged{ range: effect.1.parse::<i32>().unwrap() })},
+            "damage" => {
+                let damage_type = effects.get("damage_type").map_or(DamageType::Untyped, |t| string_to_damage_type(t));
+                eb = eb.with(InflictsDamage{ damage: effect.1.parse::<i32>().unwrap(), damage_type })
+            },
+            "damage_type" => {}, // Read along with "damage"
+            "area_of_effect" => { eb = eb.with(AreaOfEffect{ radius: effect.1.parse::<i32>().unwrap() }) },
+            "magic_mapping" => { eb = eb.with(MagicMapper{})},
+            "food" => { eb = eb.with(ProvidesFood{})},
+            "ignite" => { eb = eb.with(StartsFire{})},
+            "cloud" => { eb = eb.with(CreatesCloud{ cloud: effect.1.to_string() })},
+            "teaches_spell" => { eb = eb.with(TeachesSpell{ spell: effect.1.to_string() })},
+            "entangle" => { eb = eb.with(Entangles{ turns: effect.1.parse::<i32>().unwrap() })},
+            "charge" => { eb = eb.with(Charges{})},
+            "summon" => {
+                let count = effects.get("summon_count").map_or(1, |c| c.parse::<i32>().unwrap());
+                eb = eb.with(Summons{ mob: effect.1.to_string(), count })
+            },
+            "summon_count" => {}, // Read along with "summon"
+            _ => {
+                match string_to_status_effect(effect_name) {
+                    Some(status) => statuses.push((status, effect.1.parse::<i32>().unwrap())),
+                    None => rltk::console::log(format!("Warning: {} effect {} not implemented.", kind, effect_name)),
+                }
+            }
+        }
+    }
+    if !statuses.is_empty() {
+        eb = eb.with(InflictsStatus{ effects: statuses });
+    }
+    eb
+}
+
+/// Makes a spell from its raws template, ready to be cast. Spells have no position, and are
+/// deleted again once they've been cast.
+pub fn spawn_named_spell(raws: &RawMaster, ecs: &mut World, name: &str) -> Option<Entity> {
+    let spell_template = &raws.raws.spells[*raws.spell_index.get(name)?];
+    let mut eb = ecs.create_entity();
+    eb = eb.with(Name{ name: spell_template.name.clone() });
+    eb = eb.with(Spell{ mana_cost: spell_template.mana_cost, difficulty: spell_template.difficulty });
+    eb = add_effect_components(eb, &spell_template.effects, "spell");
+    Some(eb.build())
+}
+
+/// How much mana a spell costs to cast, if there is such a spell.
+pub fn spell_mana_cost(raws: &RawMaster, name: &str) -> Option<i32> {
+    raws.spell_index.get(name).map(|i| raws.raws.spells[*i].mana_cost)
+}
+
 /// Spawns the named item
 /// 
 /// # Arguments
@@ -163,51 +448,70 @@ pub fn spawn_named_item(raws: &RawMaster, ecs: &mut World, name: &str, pos: Spaw
         // Give the entity a name
         eb = eb.with(Name{ name: item_template.name.clone() });
 
-        eb = eb.with(crate::components::Item{});
+        // The raws weigh a whole bundle of ammunition, but rounds are fired (and stacked) one by one
+        let weight = item_template.weight.unwrap_or(0.0);
+        let weight = item_template.ammunition.as_ref().map_or(weight, |ammo| weight / i32::max(1, ammo.count) as f32);
+        eb = eb.with(crate::components::Item{ weight });
 
         // If the item is consumable, add the various consumable effects to the item
         if let Some(consumable) = &item_template.consumable {
             eb = eb.with(crate::components::Consumable{});
-            for effect in consumable.effects.iter() {
-                let effect_name = effect.0.as_str();
-                match effect_name {
-                    "provides_healing" => {
-                        eb = eb.with(ProvidesHealing{ heal_amount: effect.1.parse::<i32>().unwrap() });
-                    },
-                    "ranged" => { eb = eb.with(Ranged{ range: effect.1.parse::<i32>().unwrap() })},
-                    "damage" => { eb = eb.with(InflictsDamage{ damage: effect.1.parse::<i32>().unwrap() }) },
-                    "area_of_effect" => { eb = eb.with(AreaOfEffect{ radius: effect.1.parse::<i32>().unwrap() }) },
-                    "stunned" => { eb = eb.with(Stunned{ turns: effect.1.parse::<i32>().unwrap() }) },
-                    "magic_mapping" => { eb = eb.with(MagicMapper{})},
-                    "food" => { eb = eb.with(ProvidesFood{})},
-                    _ => {
-                        rltk::console::log(format!("Warning: consumable effect {} not implemented.", effect_name));
-                    }
-                }
-            }
+            eb = add_effect_components(eb, &consumable.effects, "consumable");
         }
 
         // If the item is a weapon, add that component
         if let Some(weapon) = &item_template.weapon {
-            eb = eb.with(Equippable{ slot: EquipmentSlot::Melee });
+            let hands = if weapon.two_handed.unwrap_or(false) { 2 } else { 1 };
+            eb = eb.with(Equippable{ slot: EquipmentSlot::Melee, hands });
             let (n_dice, die_type, bonus) = parse_dice_string(&weapon.base_damage);
-            let mut wpn = MeleeWeapon{
-                attribute: WeaponAttribute::Might,
-                damage_n_dice: n_dice,
-                damage_die_type: die_type,
-                damage_bonus: bonus,
-                hit_bonus: weapon.hit_bonus
+            let attribute = match weapon.attribute.as_str() {
+                "Quickness" => WeaponAttribute::Quickness,
+                _ => WeaponAttribute::Might
             };
-            match weapon.attribute.as_str() {
-                "Quickness" => wpn.attribute = WeaponAttribute::Quickness,
-                _ => wpn.attribute = WeaponAttribute::Might
+            let damage_type = weapon.damage_type.as_ref().map_or(DamageType::Untyped, |t| string_to_damage_type(t));
+            match weapon.range.as_str() {
+                "melee" => {
+                    eb = eb.with(MeleeWeapon{
+                        attribute,
+                        damage_type,
+                        damage_n_dice: n_dice,
+                        damage_die_type: die_type,
+                        damage_bonus: bonus,
+                        hit_bonus: weapon.hit_bonus
+                    });
+                }
+                range => {
+                    eb = eb.with(RangedWeapon{
+                        range: range.parse::<i32>().expect("Weapon range should be melee or a number"),
+                        attribute,
+                        damage_type,
+                        damage_n_dice: n_dice,
+                        damage_die_type: die_type,
+                        damage_bonus: bonus,
+                        hit_bonus: weapon.hit_bonus,
+                        ammo: weapon.ammo.clone(),
+                        thrown: weapon.thrown.unwrap_or(false),
+                    });
+                }
             }
-            eb = eb.with(wpn)
+        }
+        if let Some(ammunition) = &item_template.ammunition {
+            eb = eb.with(Ammunition{ kind: ammunition.kind.clone(), count: ammunition.count });
         }
         if let Some(wearable) = &item_template.wearable {
             let slot = string_to_slot(&wearable.slot);
-            eb = eb.with(Equippable{ slot });
+            let hands = if slot == EquipmentSlot::Shield { 1 } else { 0 };
+            eb = eb.with(Equippable{ slot, hands });
             eb = eb.with(Wearable{ slot, armour_class: wearable.armour_class });
+            if let Some(resistances) = &wearable.resistances {
+                eb = eb.with(get_resistances_component(resistances));
+            }
+        }
+        if let Some(shield) = &item_template.shield {
+            eb = eb.with(Shield{ block_chance: shield.block_chance, block_amount: shield.block_amount });
+        }
+        if let Some(lock) = &item_template.key {
+            eb = eb.with(Key{ lock: lock.clone() });
         }
 
         return Some(eb.build());
@@ -230,6 +534,7 @@ pub fn spawn_named_item(raws: &RawMaster, ecs: &mut World, name: &str, pos: Spaw
 pub fn spawn_named_mob(raws: &RawMaster, ecs: &mut World, name: &str, pos: SpawnType) -> Option<Entity> {
     if raws.mob_index.contains_key(name) {
         let mob_template = &raws.raws.mobs[raws.mob_index[name]];
+        let asleep = mob_template.can_sleep.unwrap_or(true) && ecs.write_resource::<rltk::RandomNumberGenerator>().roll_dice(1, 3) == 1;
 
         let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
 
@@ -244,7 +549,11 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs: &mut World, name: &str, pos: Spawn
         eb = eb.with(Name{ name: mob_template.name.clone() });
 
         match mob_template.ai.as_ref() {
-            "melee" => eb = eb.with(Monster{}),
+            "melee" => {
+                // Monsters start off not knowing the player's there, and some are asleep
+                let state = if asleep { Alertness::Asleep } else { Alertness::Unaware };
+                eb = eb.with(Monster{}).with(Awareness{ state });
+            }
             "bystander" => eb = eb.with(Bystander{}),
             "vendor" => eb = eb.with(Vendor{}),
             "carnivore" => eb = eb.with(Carnivore{}),
@@ -254,6 +563,8 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs: &mut World, name: &str, pos: Spawn
         if mob_template.blocks_tile {
             eb = eb.with(BlocksTile{});
         }
+        let faction = mob_template.faction.clone().unwrap_or_else(|| "Mindless".to_string());
+        eb = eb.with(Faction{ name: faction });
 
         // Set attributes
         let mut mob_fitness = 11;
@@ -294,16 +605,24 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs: &mut World, name: &str, pos: Spawn
         eb = eb.with(pools);
 
         // Set skills
-        let mut skills = Skills{ skills: HashMap::new() };
+        let mut skills = Skills{ skills: HashMap::new(), practice: HashMap::new() };
         skills.skills.insert(Skill::Melee, 1);
         skills.skills.insert(Skill::Defense, 1);
         skills.skills.insert(Skill::Magic, 1);
+        skills.skills.insert(Skill::Traps, 1);
+        skills.skills.insert(Skill::Ranged, 1);
+        skills.skills.insert(Skill::Stealth, 1);
+        skills.skills.insert(Skill::Perception, 1);
         if let Some(mobskills) = &mob_template.skills {
             for sk in mobskills.iter() {
                 match sk.0.as_str() {
                     "Melee" => { skills.skills.insert(Skill::Melee, *sk.1); },
                     "Defense" => { skills.skills.insert(Skill::Defense, *sk.1); },
                     "Magic" => { skills.skills.insert(Skill::Magic, *sk.1); },
+                    "Traps" => { skills.skills.insert(Skill::Traps, *sk.1); },
+                    "Ranged" => { skills.skills.insert(Skill::Ranged, *sk.1); },
+                    "Stealth" => { skills.skills.insert(Skill::Stealth, *sk.1); },
+                    "Perception" => { skills.skills.insert(Skill::Perception, *sk.1); },
                     _ => { rltk::console::log(format!("Unknown skill referenced: {}", sk.0)); }
                 }
             }
@@ -335,6 +654,10 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs: &mut World, name: &str, pos: Spawn
                     let (n, d, b) = parse_dice_string(&nattack.damage);
                     let attack = NaturalAttack{
                         name: nattack.name.clone(),
+                        damage_type: nattack.damage_type.as_ref().map_or(DamageType::Untyped, |t| string_to_damage_type(t)),
+                        inflicts: nattack.status.as_ref().map_or(Vec::new(), |status| {
+                            status.iter().filter_map(|(name, turns)| string_to_status_effect(name).map(|s| (s, *turns))).collect()
+                        }),
                         hit_bonus: nattack.hit_bonus,
                         damage_n_dice: n,
                         damage_die_type: d,
@@ -346,17 +669,66 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs: &mut World, name: &str, pos: Spawn
             eb = eb.with(nature);
         }
 
+        // Do they shrug off (or suffer extra from) any kinds of damage?
+        if let Some(resistances) = &mob_template.resistances {
+            eb = eb.with(get_resistances_component(resistances));
+        }
+
+        // Special abilities, which are spells the monster can cast
+        if let Some(abilities) = &mob_template.abilities {
+            let mut special = SpecialAbilities{ abilities: Vec::new() };
+            for ability in abilities.iter() {
+                if !raws.spell_index.contains_key(&ability.spell) {
+                    rltk::console::log(format!("WARNING - {} has an ability using unknown spell {}", name, ability.spell));
+                    continue;
+                }
+                special.abilities.push(SpecialAbility{
+                    spell: ability.spell.clone(),
+                    chance: ability.chance,
+                    range: ability.range,
+                    min_range: ability.min_range.unwrap_or(0.0),
+                    cooldown: ability.cooldown.unwrap_or(0),
+                    ready_in: 0,
+                    self_cast: ability.self_cast.unwrap_or(false),
+                    when_hurt: ability.when_hurt.unwrap_or(false),
+                });
+            }
+            eb = eb.with(special);
+        }
+
+        // Will they lose their nerve?
+        if let Some(morale) = &mob_template.morale {
+            let on_break = string_to_morale_break(&morale.on_break);
+            eb = eb.with(Morale{ current: morale.value, max: morale.value, on_break, broken: false });
+        }
+
         // Do they have a loot table?
         if let Some(loot) = &mob_template.loot_table {
             eb = eb.with(LootTable{table: loot.clone()});
         }
 
+        // Do they glow?
+        if let Some(light) = &mob_template.light {
+            eb = eb.with(get_light_component(light));
+        }
+
         // We've finished creating the entity - it can now be committed
         let new_mob = eb.build();
 
         // Are they equipped with anything?
         if let Some(wielding) = &mob_template.equipped {
+            // Nobody can hold a shield alongside a two-handed weapon, the same rule the player equips by
+            let two_handed = wielding.iter().any(|tag| {
+                raws.item_index.get(tag).and_then(|i| raws.raws.items[*i].weapon.as_ref())
+                    .is_some_and(|weapon| weapon.two_handed.unwrap_or(false))
+            });
             for tag in wielding.iter() {
+                if two_handed && raws.item_index.get(tag).and_then(|i| raws.raws.items[*i].wearable.as_ref())
+                    .is_some_and(|wearable| string_to_slot(&wearable.slot) == EquipmentSlot::Shield)
+                {
+                    rltk::console::log(format!("WARNING - {} can't carry {} with a two-handed weapon", mob_template.name, tag));
+                    continue;
+                }
                 spawn_named_entity(raws, ecs, tag, SpawnType::Equipped{ by: new_mob });
             }
         }
@@ -396,15 +768,45 @@ pub fn spawn_named_prop(raws: &RawMaster, ecs: &mut World, name: &str, pos: Spaw
         if let Some(door_open) = prop_template.door_open {
             eb = eb.with(Door{ open: door_open })
         }
+        if let Some(secret_door) = prop_template.secret_door {
+            if secret_door { eb = eb.with(SecretDoor{}) };
+        }
+        if let Some(lock) = &prop_template.lock {
+            eb = eb.with(Locked{ lock: lock.clone() });
+        }
+        if let Some(light) = &prop_template.light {
+            eb = eb.with(get_light_component(light));
+        }
         if let Some(entry_trigger) = &prop_template.entry_trigger {
             eb = eb.with(EntryTrigger{});
+            let mut statuses: Vec<(StatusEffect, i32)> = Vec::new();
             for effect in entry_trigger.effects.iter() {
                 match effect.0.as_str() {
-                    "damage" => { eb = eb.with(InflictsDamage{ damage: effect.1.parse::<i32>().unwrap()})},
+                    "damage" => {
+                        let damage_type = entry_trigger.effects.get("damage_type").map_or(DamageType::Untyped, |t| string_to_damage_type(t));
+                        eb = eb.with(InflictsDamage{ damage: effect.1.parse::<i32>().unwrap(), damage_type })
+                    },
+                    "damage_type" => {}, // Read along with "damage"
                     "single_activation" => { eb = eb.with(SingleActivation{}) },
-                    _ => {}
+                    "teleport" => { eb = eb.with(Teleports{}) },
+                    "alarm" => { eb = eb.with(RaisesAlarm{ radius: effect.1.parse::<i32>().unwrap() }) },
+                    "pit" => { eb = eb.with(Pitfall{}) },
+                    "net" => { eb = eb.with(Entangles{ turns: effect.1.parse::<i32>().unwrap() }) },
+                    "cloud" => { eb = eb.with(CreatesCloud{ cloud: effect.1.to_string() }) },
+                    _ => {
+                        match string_to_status_effect(effect.0) {
+                            Some(status) => statuses.push((status, effect.1.parse::<i32>().unwrap())),
+                            None => rltk::console::log(format!("Warning: trap effect {} not implemented.", effect.0)),
+                        }
+                    }
                 }
             }
+            if !statuses.is_empty() {
+                eb = eb.with(InflictsStatus{ effects: statuses });
+            }
+            if let Some(difficulty) = entry_trigger.disarm_dc {
+                eb = eb.with(Disarmable{ difficulty });
+            }
         }
 
         return Some(eb.build());
@@ -458,3 +860,43 @@ pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> RandomTable {
 
     rt
 }
+
+/// Picks a theme for a room, from those which suit the depth, the room's area and where it is on
+/// the map. Returns None if the room should stay as it is.
+pub fn get_room_theme(raws: &RawMaster, rng: &mut rltk::RandomNumberGenerator, depth: i32, area: i32, placement: &[&str]) -> Option<String> {
+    let mut rt = RandomTable::new();
+    for theme in raws.raws.room_themes.iter() {
+        if depth < theme.min_depth || depth > theme.max_depth { continue; }
+        if area < theme.min_area || area > theme.max_area.unwrap_or(i32::MAX) { continue; }
+        if let Some(wanted) = &theme.placement {
+            if !placement.contains(&wanted.as_str()) { continue; }
+        }
+        rt = rt.add(theme.name.clone(), theme.weight);
+    }
+
+    let result = rt.roll(rng);
+    if result == "None" { None } else { Some(result) }
+}
+
+/// Rolls up everything a themed room should contain, as (furniture, everything else).
+pub fn get_room_contents(raws: &RawMaster, rng: &mut rltk::RandomNumberGenerator, theme_name: &str) -> (Vec<String>, Vec<String>) {
+    let mut furniture = Vec::new();
+    let mut others = Vec::new();
+    if let Some(idx) = raws.room_theme_index.get(theme_name) {
+        let theme = &raws.raws.room_themes[*idx];
+        let mut roll_contents = |contents: &Option<Vec<super::RoomContent>>, into: &mut Vec<String>| {
+            if let Some(contents) = contents {
+                for content in contents.iter() {
+                    let (n_dice, die_type, bonus) = parse_dice_string(&content.count);
+                    for _i in 0 .. rng.roll_dice(n_dice, die_type) + bonus {
+                        into.push(content.name.clone());
+                    }
+                }
+            }
+        };
+        roll_contents(&theme.furniture, &mut furniture);
+        roll_contents(&theme.mobs, &mut others);
+        roll_contents(&theme.loot, &mut others);
+    }
+    (furniture, others)
+}
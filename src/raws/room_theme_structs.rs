@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct RoomTheme {
+    pub name: String,
+    pub weight: i32,
+    pub min_depth: i32,
+    pub max_depth: i32,
+    pub min_area: i32,
+    pub max_area: Option<i32>,
+    pub placement: Option<String>,
+    pub furniture: Option<Vec<RoomContent>>,
+    pub mobs: Option<Vec<RoomContent>>,
+    pub loot: Option<Vec<RoomContent>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RoomContent {
+    pub name: String,
+    pub count: String,
+}
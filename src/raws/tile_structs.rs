@@ -0,0 +1,28 @@
+use serde::Deserialize;
+#[derive(Deserialize, Debug)]
+pub struct Tile {
+    pub name: String,
+    pub walkable: bool,
+    pub opaque: bool,
+    pub cost: Option<f32>,
+    pub glyph: String,
+    pub fg: String,
+    pub bg: String,
+    pub connect_walls: Option<bool>,
+    pub flags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TileTheme {
+    pub name: String,
+    pub tiles: Vec<TileThemeEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TileThemeEntry {
+    pub name: String,
+    pub glyph: Option<String>,
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub connect_walls: Option<bool>,
+}
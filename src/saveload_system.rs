@@ -28,6 +28,29 @@ pub fn save_game(ecs: &mut World) {
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
+    // Chases and reactions can only be saved if whoever they're aimed at is saved too
+    {
+        let entities = ecs.entities();
+        let markers = ecs.read_storage::<SimpleMarker<SerializeMe>>();
+        let saved = |target: Entity| entities.is_alive(target) && markers.get(target).is_some();
+        let mut chasing = ecs.write_storage::<Chasing>();
+        let stale: Vec<Entity> = (&entities, &chasing).join()
+            .filter(|(_entity, chase)| !saved(chase.target))
+            .map(|(entity, _chase)| entity)
+            .collect();
+        for entity in stale.iter() {
+            chasing.remove(*entity);
+        }
+        let mut wants_react = ecs.write_storage::<WantsToReact>();
+        let stale: Vec<Entity> = (&entities, &wants_react).join()
+            .filter(|(_entity, decision)| !saved(decision.target))
+            .map(|(entity, _decision)| entity)
+            .collect();
+        for entity in stale.iter() {
+            wants_react.remove(*entity);
+        }
+    }
+
     // Actually serialize
     {
         let data = (ecs.entities(), ecs.read_storage::<SimpleMarker<SerializeMe>>() );
@@ -37,14 +60,18 @@ pub fn save_game(ecs: &mut World) {
         serialize_individually!(ecs, serializer, data,
             Position, Renderable, Player, Viewshed, Monster, Name, BlocksTile,
             SufferDamage, WantsToMelee, Item, Consumable, Ranged,
-            InflictsDamage, AreaOfEffect, Stunned, ProvidesHealing, InBackpack,
+            InflictsDamage, AreaOfEffect, ProvidesHealing, InBackpack,
             WantsToPickupItem, WantsToUseItem, WantsToDropItem,
             SerializationHelper, Equippable, Equipped, MeleeWeapon,
-            Wearable, WantsToRemoveItem, ParticleLifetime, HungerClock,
+            Wearable, Shield, WantsToRemoveItem, ParticleLifetime, HungerClock,
             ProvidesFood, MagicMapper, Hidden, EntryTrigger, EntityMoved,
             SingleActivation, RemembersPlayer, BlocksVisibility, Door,
-            Bystander, Vendor, Quips, Attributes, Skills, Pools,
-            NaturalAttackDefense, LootTable, Carnivore, Herbivore
+            Bystander, Vendor, Quips, Attributes, Skills, Pools, Encumbrance, Morale, Awareness, Faction, Grudges,
+            NaturalAttackDefense, LootTable, Carnivore, Herbivore, SecretDoor, Locked, Key, LightSource, Swimming,
+            StartsFire, Burning, CreatesCloud, Teleports, RaisesAlarm, Pitfall, Entangles,
+            Disarmable, WantsToDisarm, RangedWeapon, Ammunition, WantsToShoot,
+            WantsToSearch, Resistances, StatusEffects, InflictsStatus, KnownSpells, TeachesSpell, SpecialAbilities,
+            Chasing, WantsToReact
         );
     }
 }
@@ -89,14 +116,18 @@ pub fn load_game(ecs: &mut World) {
         deserialize_individually!(ecs, de, d,
             Position, Renderable, Player, Viewshed, Monster, Name, BlocksTile,
             SufferDamage, WantsToMelee, Item, Consumable, Ranged,
-            InflictsDamage, AreaOfEffect, Stunned, ProvidesHealing, InBackpack,
+            InflictsDamage, AreaOfEffect, ProvidesHealing, InBackpack,
             WantsToPickupItem, WantsToUseItem, WantsToDropItem,
             SerializationHelper, Equippable, Equipped, MeleeWeapon,
-            Wearable, WantsToRemoveItem, ParticleLifetime, HungerClock,
+            Wearable, Shield, WantsToRemoveItem, ParticleLifetime, HungerClock,
             ProvidesFood, MagicMapper, Hidden, EntryTrigger, EntityMoved,
             SingleActivation, RemembersPlayer, BlocksVisibility, Door,
-            Bystander, Vendor, Quips, Attributes, Skills, Pools,
-            NaturalAttackDefense, LootTable, Carnivore, Herbivore
+            Bystander, Vendor, Quips, Attributes, Skills, Pools, Encumbrance, Morale, Awareness, Faction, Grudges,
+            NaturalAttackDefense, LootTable, Carnivore, Herbivore, SecretDoor, Locked, Key, LightSource, Swimming,
+            StartsFire, Burning, CreatesCloud, Teleports, RaisesAlarm, Pitfall, Entangles,
+            Disarmable, WantsToDisarm, RangedWeapon, Ammunition, WantsToShoot,
+            WantsToSearch, Resistances, StatusEffects, InflictsStatus, KnownSpells, TeachesSpell, SpecialAbilities,
+            Chasing, WantsToReact
         );
     }
 
@@ -4,17 +4,22 @@ use super::{
     Player, Renderable, Name, Position, Viewshed, Rect,
     SerializeMe, random_table::RandomTable, HungerClock, HungerState, Map,
     TileType, raws::*, Attributes, Attribute, Skills, Skill, Pools, Pool,
+    LightSource, KnownSpells, Encumbrance, EncumbranceState, Faction, Grudges,
 };
-use crate::{ attr_bonus, player_hp_at_level, mana_at_level};
+use crate::{ attr_bonus, player_hp_at_level, mana_at_level, carry_capacity };
 use specs::saveload::{MarkedBuilder, SimpleMarker};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Spawns the player and returns their entity object.
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
-    let mut skills = Skills{ skills: HashMap::new() };
+    let mut skills = Skills{ skills: HashMap::new(), practice: HashMap::new() };
     skills.skills.insert(Skill::Melee, 1);
     skills.skills.insert(Skill::Defense, 1);
     skills.skills.insert(Skill::Magic, 1);
+    skills.skills.insert(Skill::Traps, 1);
+    skills.skills.insert(Skill::Ranged, 1);
+    skills.skills.insert(Skill::Stealth, 1);
+    skills.skills.insert(Skill::Perception, 1);
 
     let player = ecs
         .create_entity()
@@ -27,6 +32,7 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         })
         .with(Player{})
         .with(Viewshed{ visible_tiles: Vec::new(), range: 8, dirty: true })
+        .with(LightSource{ color: RGB::from_f32(1.0, 1.0, 0.6), range: 6 })
         .with(Name{ name: "Player".to_string() })
         .with(HungerClock{
             state: HungerState::WellFed,
@@ -39,6 +45,10 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
             intelligence: Attribute{ base: 11, modifiers: 0, bonus: attr_bonus(11)}
         })
         .with(skills)
+        .with(KnownSpells{ spells: vec!["Magic Missile".to_string()] })
+        .with(Encumbrance{ weight: 0.0, capacity: carry_capacity(11), state: EncumbranceState::Unburdened })
+        .with(Faction{ name: "Player".to_string() })
+        .with(Grudges{ factions: HashSet::new() })
         .with(Pools{
             hit_points: Pool{
                 current: player_hp_at_level(11, 1),
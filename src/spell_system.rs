@@ -0,0 +1,154 @@
+use specs::prelude::*;
+use super::{
+    WantsToCastSpell, WantsToUseItem, Spell, Pools, Attributes, Skills, Skill, Name, RunState,
+    gamelog::GameLog, skill_bonus, improve_skills, WantsToUseAbility, WantsToSummon, Map, Position,
+    Awareness, Alertness,
+    raws::{RAWS, SpawnType, spawn_named_spell, spawn_named_mob},
+};
+use rltk::Point;
+
+/// Makes the spells for the abilities monsters have chosen to use, so they can be cast just like
+/// the player's.
+pub fn ready_abilities(ecs: &mut World) {
+    let chosen: Vec<(Entity, WantsToUseAbility)> = {
+        let entities = ecs.entities();
+        let wants_ability = ecs.read_storage::<WantsToUseAbility>();
+        (&entities, &wants_ability).join().map(|(entity, ability)| (entity, ability.clone())).collect()
+    };
+    ecs.write_storage::<WantsToUseAbility>().clear();
+
+    for (caster, ability) in chosen.iter() {
+        if let Some(spell) = spawn_named_spell(&RAWS.lock().unwrap(), ecs, &ability.spell) {
+            ecs.write_storage::<WantsToCastSpell>()
+                .insert(*caster, WantsToCastSpell{ spell, target: ability.target })
+                .expect("Unable to insert intent");
+        }
+    }
+}
+
+/// Brings in the allies summoned by spells this turn, on free tiles around whoever called them.
+pub fn summon_allies(ecs: &mut World) {
+    let mut to_spawn: Vec<(String, i32, i32)> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let wants_summon = ecs.read_storage::<WantsToSummon>();
+        let positions = ecs.read_storage::<Position>();
+        let mut map = ecs.fetch_mut::<Map>();
+        let player_pos = ecs.fetch::<Point>();
+        for (_entity, summon, pos) in (&entities, &wants_summon, &positions).join() {
+            let mut remaining = summon.count;
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)].iter() {
+                if remaining < 1 { break; }
+                let (x, y) = (pos.x + dx, pos.y + dy);
+                if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 { continue; }
+                let idx = map.xy_idx(x, y);
+                // The player doesn't block their tile, so don't summon anything on top of them
+                if map.blocked[idx] || (x == player_pos.x && y == player_pos.y) { continue; }
+                map.blocked[idx] = true;
+                to_spawn.push((summon.mob.clone(), x, y));
+                remaining -= 1;
+            }
+        }
+    }
+    ecs.write_storage::<WantsToSummon>().clear();
+
+    for (mob, x, y) in to_spawn.iter() {
+        let summoned = spawn_named_mob(&RAWS.lock().unwrap(), ecs, mob, SpawnType::AtPosition{ x: *x, y: *y });
+        // They know exactly why they've been called
+        if let Some(summoned) = summoned {
+            if let Some(aware) = ecs.write_storage::<Awareness>().get_mut(summoned) {
+                aware.state = Alertness::Alert;
+            }
+        }
+    }
+}
+
+/// Casting is a roll of intelligence and magic skill against how tricky the spell is. The mana
+/// is spent either way, and a spell which works goes off just like a consumable item.
+pub struct SpellCastSystem {}
+
+impl<'a> System<'a> for SpellCastSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteStorage<'a, WantsToCastSpell>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, Spell>,
+        WriteStorage<'a, Pools>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Skills>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities, player_entity, mut wants_cast, mut wants_use, spells, mut pools,
+            attributes, mut skills, names, mut gamelog, mut rng,
+        ) = data;
+
+        for (entity, cast) in (&entities, &wants_cast).join() {
+            let is_player = entity == *player_entity;
+            let spell_name = names.get(cast.spell).map_or("spell".to_string(), |n| n.name.clone());
+            let (spell, stats) = match (spells.get(cast.spell), pools.get_mut(entity)) {
+                (Some(spell), Some(stats)) => (spell, stats),
+                _ => continue,
+            };
+            if stats.mana.current < spell.mana_cost {
+                if is_player {
+                    gamelog.entries.push(format!("You don't have enough mana to cast {}.", spell_name));
+                }
+                entities.delete(cast.spell).expect("Unable to delete spell");
+                continue;
+            }
+            stats.mana.current -= spell.mana_cost;
+
+            let attribute_bonus = attributes.get(entity).map_or(0, |a| a.intelligence.bonus);
+            let magic_skill = skills.get(entity).map_or(0, |s| skill_bonus(Skill::Magic, s));
+            if rng.roll_dice(1, 20) + attribute_bonus + magic_skill >= spell.difficulty {
+                if is_player {
+                    gamelog.entries.push(format!("You cast {}.", spell_name));
+                }
+                wants_use.insert(entity, WantsToUseItem{ item: cast.spell, target: cast.target }).expect("Unable to insert intent");
+                if is_player {
+                    improve_skills(&[(entity, Skill::Magic)], &mut skills, *player_entity, &mut gamelog);
+                }
+            } else {
+                if is_player {
+                    gamelog.entries.push(format!("You try to cast {}, but it fizzles.", spell_name));
+                }
+                entities.delete(cast.spell).expect("Unable to delete spell");
+            }
+        }
+
+        wants_cast.clear();
+    }
+}
+
+/// Mana trickles back a little at a time; the cleverer you are, the faster it returns.
+pub struct ManaRegenSystem {}
+
+impl<'a> System<'a> for ManaRegenSystem {
+    type SystemData = (
+        WriteStorage<'a, Pools>,
+        ReadStorage<'a, Attributes>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut pools, attributes, runstate, mut rng) = data;
+
+        if *runstate != RunState::MonsterTurn { return; }
+
+        for (stats, attr) in (&mut pools, &attributes).join() {
+            if stats.mana.current >= stats.mana.max { continue; }
+            let chance = i32::max(2, 6 - attr.intelligence.bonus);
+            if rng.roll_dice(1, chance) == 1 {
+                stats.mana.current += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use specs::prelude::*;
+use super::{
+    StatusEffects, StatusEffect, Pools, SufferDamage, DamageType, RunState, Name, Map, Position,
+    Viewshed, gamelog::GameLog, particle_system::ParticleBuilder,
+};
+
+const DIRECTIONS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// What someone's status effects leave them able to do this turn.
+#[derive(PartialEq, Copy, Clone)]
+pub enum Impairment {
+    /// Free to act as they like
+    None,
+    /// Stunned, or too slow to act this turn
+    LosesTurn,
+    /// Confused, and blundering off in a random direction
+    Staggers,
+    /// Too frightened to do anything but run away
+    Flees,
+}
+
+/// Works out how someone's status effects hamper them this turn. Slowed creatures lose every
+/// other turn, and confused ones stagger about half the time.
+pub fn impairment(status: Option<&StatusEffects>, rng: &mut rltk::RandomNumberGenerator) -> Impairment {
+    let status = match status {
+        None => return Impairment::None,
+        Some(status) => status,
+    };
+    if status.has(StatusEffect::Stun) || status.turns(StatusEffect::Slow).is_some_and(|turns| turns % 2 == 1) {
+        Impairment::LosesTurn
+    } else if status.has(StatusEffect::Confusion) && rng.roll_dice(1, 2) == 1 {
+        Impairment::Staggers
+    } else if status.has(StatusEffect::Fear) {
+        Impairment::Flees
+    } else {
+        Impairment::None
+    }
+}
+
+/// A random step for someone staggering about in confusion.
+pub fn stagger_direction(rng: &mut rltk::RandomNumberGenerator) -> (i32, i32) {
+    DIRECTIONS[(rng.roll_dice(1, 8) - 1) as usize]
+}
+
+/// Picks where a confused creature at `idx` blunders off to, if anywhere.
+pub fn stagger_destination(map: &Map, idx: usize, rng: &mut rltk::RandomNumberGenerator) -> Option<usize> {
+    let (dx, dy) = stagger_direction(rng);
+    let (x, y) = (idx as i32 % map.width + dx, idx as i32 / map.width + dy);
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 { return None; }
+    let dest = map.xy_idx(x, y);
+    if map.blocked[dest] { None } else { Some(dest) }
+}
+
+/// Picks the step which best gets a frightened creature at `idx` away from `threats`.
+pub fn flee_destination(map: &mut Map, idx: usize, threats: &[usize]) -> Option<usize> {
+    let flee_map = rltk::DijkstraMap::new(map.width as usize, map.height as usize, threats, &*map, 100.0);
+    rltk::DijkstraMap::find_highest_exit(&flee_map, idx, &*map).filter(|dest| !map.blocked[*dest])
+}
+
+/// Called when someone caught in a net tries to move. They spend the turn struggling instead, and
+/// get free once they've struggled enough. Returns true if the turn went on struggling.
+pub fn struggle(status_effects: &mut WriteStorage<StatusEffects>, entity: Entity) -> bool {
+    let status = match status_effects.get_mut(entity) {
+        None => return false,
+        Some(status) => status,
+    };
+    let net = match status.effects.iter_mut().find(|e| e.effect == StatusEffect::Entangled) {
+        None => return false,
+        Some(net) => net,
+    };
+    net.turns -= 1;
+    if net.turns < 1 {
+        status.effects.retain(|e| e.effect != StatusEffect::Entangled);
+    }
+    true
+}
+
+/// Called once the player has acted. Haste counts down with the player's own actions, rather than
+/// with the passing turns, and every other one of them is free. Returns true if the monsters
+/// don't get to move this time.
+pub fn hasted_extra_action(ecs: &mut World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut status = ecs.write_storage::<StatusEffects>();
+    let status = match status.get_mut(*player_entity) {
+        None => return false,
+        Some(status) => status,
+    };
+    let haste = match status.effects.iter_mut().find(|e| e.effect == StatusEffect::Haste) {
+        None => return false,
+        Some(haste) => haste,
+    };
+    haste.turns -= 1;
+    let free_action = haste.turns % 2 == 1;
+    if haste.turns < 1 {
+        status.effects.retain(|e| e.effect != StatusEffect::Haste);
+        ecs.fetch_mut::<GameLog>().entries.push("You are no longer hasted.".to_string());
+    }
+    free_action
+}
+
+/// Applies poison and regeneration each turn, and counts down everyone's status effects.
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, StatusEffects>,
+        WriteStorage<'a, Pools>,
+        WriteStorage<'a, SufferDamage>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Name>,
+        ReadExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Viewshed>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, ParticleBuilder>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities, mut status_effects, mut pools, mut inflict_damage, runstate, player_entity,
+            names, map, positions, mut viewsheds, mut gamelog, mut particle_builder,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn { return; }
+
+        let mut cured: Vec<Entity> = Vec::new();
+        for (entity, status) in (&entities, &mut status_effects).join() {
+            let is_player = entity == *player_entity;
+            let pos = positions.get(entity);
+            let seen = is_player || pos.is_some_and(|pos| map.visible_tiles[map.xy_idx(pos.x, pos.y)]);
+
+            for active in status.effects.iter_mut() {
+                match active.effect {
+                    StatusEffect::Poison => {
+                        SufferDamage::new_damage(&mut inflict_damage, entity, active.potency, DamageType::Poison, false);
+                        if let (true, Some(pos)) = (seen, pos) {
+                            particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::GREEN), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('♣'), 200.0);
+                        }
+                    }
+                    StatusEffect::Regeneration => {
+                        if let Some(stats) = pools.get_mut(entity) {
+                            stats.hit_points.current = i32::min(stats.hit_points.max, stats.hit_points.current + active.potency);
+                        }
+                    }
+                    StatusEffect::Blindness => {
+                        if let Some(viewshed) = viewsheds.get_mut(entity) {
+                            viewshed.dirty = true;
+                        }
+                    }
+                    _ => {}
+                }
+                // The player's haste counts down as they act instead, and nets only loosen as their
+                // victims struggle
+                if !(is_player && active.effect == StatusEffect::Haste) && active.effect != StatusEffect::Entangled {
+                    active.turns -= 1;
+                }
+            }
+
+            for expired in status.effects.iter().filter(|e| e.turns < 1) {
+                if expired.effect == StatusEffect::Blindness {
+                    if let Some(viewshed) = viewsheds.get_mut(entity) {
+                        viewshed.dirty = true;
+                    }
+                }
+                if is_player {
+                    gamelog.entries.push(format!("You are no longer {}.", expired.effect.describe().to_lowercase()));
+                } else if let (true, Some(name)) = (seen, names.get(entity)) {
+                    gamelog.entries.push(format!("{} is no longer {}.", name.name, expired.effect.describe().to_lowercase()));
+                }
+            }
+            status.effects.retain(|e| e.turns > 0);
+            if status.effects.is_empty() {
+                cured.push(entity);
+            }
+        }
+
+        for entity in cured.iter() {
+            status_effects.remove(*entity);
+        }
+    }
+}
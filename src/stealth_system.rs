@@ -0,0 +1,143 @@
+use specs::prelude::*;
+use std::collections::{BinaryHeap, HashMap};
+use super::{
+    Awareness, Alertness, Viewshed, Position, Attributes, Skills, Equipped, Wearable, Name, Item,
+    Map, gamelog::GameLog, tile_walkable, stealth_check,
+};
+use rltk::Point;
+
+/// How far (roughly, in tiles) the sounds the player makes carry.
+pub const FOOTSTEP_NOISE: i32 = 3;
+pub const DOOR_NOISE: i32 = 6;
+pub const RANGED_NOISE: i32 = 5;
+pub const MELEE_NOISE: i32 = 8;
+
+const DIRECTIONS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// Collects the noises made this turn, for the `StealthSystem` to spread around the map.
+pub struct NoiseBuilder {
+    noises: Vec<(Point, i32)>,
+}
+
+impl NoiseBuilder {
+    pub fn new() -> NoiseBuilder {
+        NoiseBuilder{ noises: Vec::new() }
+    }
+
+    pub fn make(&mut self, x: i32, y: i32, volume: i32) {
+        self.noises.push((Point::new(x, y), volume));
+    }
+}
+
+/// How loud a noise still is on each tile it reaches. Sound fades a step at a time along open
+/// ground, and much faster through closed doors; it doesn't get through walls at all.
+fn spread_noise(map: &Map, origin: Point, volume: i32) -> HashMap<usize, i32> {
+    let start = map.xy_idx(origin.x, origin.y);
+    let mut heard: HashMap<usize, i32> = HashMap::new();
+    let mut frontier: BinaryHeap<(i32, usize)> = BinaryHeap::new();
+    heard.insert(start, volume);
+    frontier.push((volume, start));
+
+    while let Some((loudness, idx)) = frontier.pop() {
+        if heard.get(&idx).is_some_and(|l| *l > loudness) { continue; }
+        let (x, y) = (idx as i32 % map.width, idx as i32 / map.width);
+        for (dx, dy) in DIRECTIONS.iter() {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || nx > map.width - 1 || ny < 0 || ny > map.height - 1 { continue; }
+            let next = map.xy_idx(nx, ny);
+            if !tile_walkable(map.tiles[next]) { continue; }
+            let remaining = loudness - if map.closed_doors.contains(&next) { 3 } else { 1 };
+            if remaining < 1 || heard.get(&next).is_some_and(|l| *l >= remaining) { continue; }
+            heard.insert(next, remaining);
+            frontier.push((remaining, next));
+        }
+    }
+    heard
+}
+
+/// How much harder someone's armour makes it to move quietly: a point for every 10 lbs of it
+/// they're wearing.
+pub fn armour_noise<D: std::ops::Deref<Target = specs::storage::MaskedStorage<Equipped>>>(
+    wearer: Entity,
+    equipped: &Storage<Equipped, D>,
+    wearables: &ReadStorage<Wearable>,
+    items: &ReadStorage<Item>,
+) -> i32 {
+    let worn: f32 = (equipped, wearables, items).join()
+        .filter(|(eq, _armour, _item)| eq.owner == wearer)
+        .map(|(_eq, _armour, item)| item.weight)
+        .sum();
+    worn as i32 / 10
+}
+
+/// Decides whether monsters who haven't noticed the player yet do so, either by hearing the noise
+/// they've been making or by spotting them. Sleeping monsters can only be woken by noise.
+pub struct StealthSystem {}
+
+impl<'a> System<'a> for StealthSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, Point>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, NoiseBuilder>,
+        WriteStorage<'a, Awareness>,
+        ReadStorage<'a, Viewshed>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Wearable>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        ReadStorage<'a, Item>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities, player_entity, player_pos, map, mut noise, mut awareness, viewsheds, positions,
+            attributes, skills, equipped, wearables, names, mut gamelog, mut rng, items,
+        ) = data;
+
+        let noises: Vec<HashMap<usize, i32>> = noise.noises.drain(..)
+            .map(|(origin, volume)| spread_noise(&map, origin, volume))
+            .collect();
+        let player_attributes = match attributes.get(*player_entity) {
+            None => return,
+            Some(attr) => attr,
+        };
+        let player_skills = skills.get(*player_entity);
+        let armour_penalty = armour_noise(*player_entity, &equipped, &wearables, &items);
+
+        for (entity, aware, pos) in (&entities, &mut awareness, &positions).join() {
+            if aware.state == Alertness::Alert { continue; }
+            let idx = map.xy_idx(pos.x, pos.y);
+
+            // Sleepers take more waking than those who are merely unaware
+            let mut noticed = false;
+            if let Some(loudness) = noises.iter().filter_map(|heard| heard.get(&idx)).max() {
+                let difficulty = loudness + if aware.state == Alertness::Asleep { 5 } else { 10 };
+                noticed = !stealth_check(&mut rng, player_attributes, player_skills, armour_penalty, difficulty);
+            }
+            // Being seen is more likely up close
+            if !noticed && aware.state == Alertness::Unaware && viewsheds.get(entity).is_some_and(|v| v.visible_tiles.contains(&*player_pos)) {
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos) as i32;
+                noticed = !stealth_check(&mut rng, player_attributes, player_skills, armour_penalty, i32::max(5, 16 - distance));
+            }
+
+            if noticed {
+                if map.visible_tiles[idx] {
+                    let name = names.get(entity).map_or("Something".to_string(), |n| n.name.clone());
+                    gamelog.entries.push(match aware.state {
+                        Alertness::Asleep => format!("{} wakes up!", name),
+                        _ => format!("{} notices you!", name),
+                    });
+                }
+                aware.state = Alertness::Alert;
+            }
+        }
+    }
+}
+
@@ -1,17 +1,24 @@
 use specs::prelude::*;
 use super::{
     EntityMoved, Position, EntryTrigger, Hidden, Map, Name, gamelog::GameLog,
-    InflictsDamage, particle_system::ParticleBuilder, SufferDamage,
-    SingleActivation, Renderable,
+    InflictsDamage, particle_system::ParticleBuilder, SufferDamage, DamageType,
+    SingleActivation, Renderable, RunState, Viewshed, Teleports, RaisesAlarm, Pitfall,
+    Entangles, StatusEffect, CreatesCloud, RemembersPlayer, WantsToDisarm, Disarmable, Attributes,
+    Skills, Skill, skill_bonus, improve_skills, Equipped, InBackpack, InflictsStatus, StatusEffects, cloud_system::add_cloud, map::tile_walkable,
+    Chasing, Awareness, Alertness, hazard_system::{witness, remove_with_belongings},
 };
+use rltk::Point;
 
+/// Sets off traps when something steps on them (or fumbles an attempt to disarm them), and
+/// handles disarming.
 pub struct TriggerSystem{}
 
 impl<'a> System<'a> for TriggerSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
-        ReadExpect<'a, Map>,
+        WriteExpect<'a, Map>,
         WriteStorage<'a, EntityMoved>,
-        ReadStorage<'a, Position>,
+        WriteStorage<'a, Position>,
         WriteStorage<'a, EntryTrigger>,
         WriteStorage<'a, Hidden>,
         ReadStorage<'a, Name>,
@@ -22,53 +29,214 @@ impl<'a> System<'a> for TriggerSystem {
         WriteStorage<'a, SufferDamage>,
         WriteStorage<'a, SingleActivation>,
         WriteStorage<'a, Renderable>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, Point>,
+        WriteExpect<'a, RunState>,
+        WriteStorage<'a, Viewshed>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        (
+            ReadStorage<'a, Teleports>,
+            ReadStorage<'a, RaisesAlarm>,
+            ReadStorage<'a, Pitfall>,
+            ReadStorage<'a, Entangles>,
+            ReadStorage<'a, CreatesCloud>,
+            WriteStorage<'a, RemembersPlayer>,
+            ReadStorage<'a, Equipped>,
+            ReadStorage<'a, InBackpack>,
+        ),
+        (
+            WriteStorage<'a, WantsToDisarm>,
+            ReadStorage<'a, Disarmable>,
+            ReadStorage<'a, Attributes>,
+            WriteStorage<'a, Skills>,
+        ),
+        (
+            ReadStorage<'a, InflictsStatus>,
+            WriteStorage<'a, StatusEffects>,
+            WriteStorage<'a, Chasing>,
+            WriteStorage<'a, Awareness>,
+        ),
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
-            map, mut entity_moved, positions, mut entry_triggers, mut hidden,
+            mut map, mut entity_moved, mut positions, mut entry_triggers, mut hidden,
             names, entities, mut gamelog, inflicts_damage, mut particle_builder,
-            mut inflict_damage, mut single_activation, mut renderable,
+            mut inflict_damage, mut single_activation, mut renderable, player_entity,
+            mut player_pos, mut runstate, mut viewsheds, mut rng,
+            (teleports, alarms, pitfalls, entangles, creates_cloud, mut remembers_player, equipped, backpack),
+            (mut wants_disarm, disarmable, attributes, mut skills),
+            (inflicts_status, mut status_effects, mut chasing, mut awareness),
         ) = data;
 
         let mut deactivate_entities: Vec<Entity> = Vec::new();
+        // Traps which have gone off, and who set them off
+        let mut sprung: Vec<(Entity, Entity)> = Vec::new();
+
         // For each entity which moved, look at its final position
         for (entity, mut _entity_moved, pos) in (&entities, &mut entity_moved, &positions).join() {
             let idx = map.xy_idx(pos.x, pos.y);
             // Iterate through all other entities on that tile to look for triggered entities.
             for entity_id in map.tile_content[idx].iter() {
-                if entity != *entity_id { // Don't bother to check whether you are a trap.
-                    let maybe_trigger = entry_triggers.get(*entity_id);
-                    match maybe_trigger {
-                        None => {},
-                        Some(_trigger) => {
-                            // We triggered it!
-                            let name = names.get(*entity_id);
-                            if let Some(name) = name {
-                                gamelog.entries.push(format!("{} triggers!", &name.name));
-                            }
-
-                            hidden.remove(*entity_id); // The trap is no longer hidden.
-
-                            // If the trap is damaging, inflict damage
-                            let damages = inflicts_damage.get(*entity_id);
-                            if let Some(damages) = damages {
-                                particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
-                                SufferDamage::new_damage(&mut inflict_damage, entity, damages.damage);
-                            }
-
-                            // If it is a single activation, mark it for deactivation
-                            let sa = single_activation.get(*entity_id);
-                            if let Some(_sa) = sa {
-                                deactivate_entities.push(*entity_id);
-                            }
+                if entity != *entity_id && entry_triggers.get(*entity_id).is_some() { // Don't bother to check whether you are a trap.
+                    sprung.push((*entity_id, entity));
+                }
+            }
+        }
+
+        // Disarming is a roll of quickness and trap skill against how fiddly the trap is
+        for (entity, wants) in (&entities, &wants_disarm).join() {
+            if entry_triggers.get(wants.trap).is_none() { continue; }
+            let is_player = entity == *player_entity;
+            let trap_name = names.get(wants.trap).map_or("trap".to_string(), |n| n.name.clone());
+            let difficulty = match disarmable.get(wants.trap) {
+                None => {
+                    if is_player {
+                        gamelog.entries.push(format!("You can't see any way to disarm the {}.", trap_name));
+                    }
+                    continue;
+                }
+                Some(disarmable) => disarmable.difficulty,
+            };
+            let attribute_bonus = attributes.get(entity).map_or(0, |a| a.quickness.bonus);
+            let trap_skill = skills.get(entity).map_or(0, |s| skill_bonus(Skill::Traps, s));
+            if rng.roll_dice(1, 20) + attribute_bonus + trap_skill >= difficulty {
+                if is_player {
+                    gamelog.entries.push(format!("You disarm the {}.", trap_name));
+                    improve_skills(&[(entity, Skill::Traps)], &mut skills, *player_entity, &mut gamelog);
+                }
+                deactivate_entities.push(wants.trap);
+            } else {
+                if is_player {
+                    gamelog.entries.push(format!("You fumble with the {} and set it off!", trap_name));
+                }
+                sprung.push((wants.trap, entity));
+            }
+        }
+        wants_disarm.clear();
+
+        let mut fallen: Vec<Entity> = Vec::new();
+        for (trap, victim) in sprung.iter() {
+            if deactivate_entities.contains(trap) { continue; } // A single use trap has already gone off
+            let pos = match positions.get(*victim) {
+                None => continue,
+                Some(pos) => Point::new(pos.x, pos.y),
+            };
+            let is_player = *victim == *player_entity;
+            let trap_idx = match positions.get(*trap) {
+                None => continue,
+                Some(trap_pos) => map.xy_idx(trap_pos.x, trap_pos.y),
+            };
+            let (victim_name, seen) = witness(*victim, *player_entity, &names, &map, trap_idx);
+
+            // We triggered it!
+            let name = names.get(*trap);
+            if let (true, Some(name)) = (seen, name) {
+                gamelog.entries.push(format!("{} triggers!", &name.name));
+            }
+
+            hidden.remove(*trap); // The trap is no longer hidden.
+
+            // If the trap is damaging, inflict damage
+            let damages = inflicts_damage.get(*trap);
+            if let Some(damages) = damages {
+                particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
+                SufferDamage::new_damage(&mut inflict_damage, *victim, damages.damage, damages.damage_type, false);
+            }
+
+            if let Some(inflicts) = inflicts_status.get(*trap) {
+                for (effect, turns) in inflicts.effects.iter() {
+                    StatusEffects::apply(&mut status_effects, *victim, *effect, *turns);
+                    if seen {
+                        match &victim_name {
+                            None => gamelog.entries.push(format!("You are {}!", effect.describe().to_lowercase())),
+                            Some(name) => gamelog.entries.push(format!("{} is {}!", name, effect.describe().to_lowercase())),
+                        }
+                    }
+                }
+            }
+
+            if let Some(net) = entangles.get(*trap) {
+                StatusEffects::apply(&mut status_effects, *victim, StatusEffect::Entangled, net.turns);
+                if seen {
+                    match &victim_name {
+                        None => gamelog.entries.push("You are caught in a net!".to_string()),
+                        Some(name) => gamelog.entries.push(format!("{} is caught in a net!", name)),
+                    }
+                }
+            }
+
+            if let Some(cloud) = creates_cloud.get(*trap) {
+                add_cloud(&mut map, trap_idx, &cloud.cloud);
+            }
+
+            if let Some(alarm) = alarms.get(*trap) {
+                gamelog.entries.push("An alarm bell clangs loudly!".to_string());
+                let alarm_pos = Point::new(trap_idx as i32 % map.width, trap_idx as i32 / map.width);
+                for (listener, listener_pos, remembers) in (&entities, &positions, &mut remembers_player).join() {
+                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(alarm_pos, Point::new(listener_pos.x, listener_pos.y));
+                    if distance <= alarm.radius as f32 {
+                        remembers.memory = i32::max(remembers.max_memory, remembers.memory);
+                        chasing.insert(listener, Chasing{ target: *player_entity, last_seen: alarm_pos }).expect("Unable to insert chase");
+                        if let Some(aware) = awareness.get_mut(listener) {
+                            aware.state = Alertness::Alert;
                         }
-                    }    
+                    }
+                }
+            }
+
+            if teleports.get(*trap).is_some() {
+                // The player doesn't block their tile, so make sure nobody lands on them
+                let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+                let destinations: Vec<usize> = (0..map.tiles.len())
+                    .filter(|idx| tile_walkable(map.tiles[*idx]) && !map.blocked[*idx] && !map.is_hazardous(*idx) && *idx != trap_idx && *idx != player_idx)
+                    .collect();
+                if !destinations.is_empty() {
+                    let destination = destinations[(rng.roll_dice(1, destinations.len() as i32) - 1) as usize];
+                    let (x, y) = (destination as i32 % map.width, destination as i32 / map.width);
+                    if let Some(victim_pos) = positions.get_mut(*victim) {
+                        victim_pos.x = x;
+                        victim_pos.y = y;
+                    }
+                    if let Some(viewshed) = viewsheds.get_mut(*victim) {
+                        viewshed.dirty = true;
+                    }
+                    if is_player {
+                        player_pos.x = x;
+                        player_pos.y = y;
+                        gamelog.entries.push("The world lurches, and you find yourself somewhere else!".to_string());
+                    } else if let (true, Some(name)) = (seen, &victim_name) {
+                        gamelog.entries.push(format!("{} vanishes!", name));
+                    }
+                }
+            }
+
+            if pitfalls.get(*trap).is_some() {
+                if is_player {
+                    gamelog.entries.push("The floor gives way beneath you!".to_string());
+                    SufferDamage::new_damage(&mut inflict_damage, *victim, rng.roll_dice(1, 6), DamageType::Bludgeoning, false);
+                    *runstate = RunState::NextLevel;
+                } else {
+                    if let (true, Some(name)) = (seen, &victim_name) {
+                        gamelog.entries.push(format!("{} falls into a pit!", name));
+                    }
+                    fallen.push(*victim);
                 }
             }
+
+            // If it is a single activation, mark it for deactivation
+            let sa = single_activation.get(*trap);
+            if let Some(_sa) = sa {
+                deactivate_entities.push(*trap);
+            }
+        }
+
+        // Whatever falls down a pit is gone from this level
+        for victim in fallen.iter() {
+            remove_with_belongings(&entities, *victim, &equipped, &backpack);
         }
 
-        // Deactivate any single use traps
+        // Deactivate any single use (or disarmed) traps
         for trap in deactivate_entities.iter() {
             single_activation.remove(*trap);
             entry_triggers.remove(*trap);
@@ -81,4 +249,4 @@ impl<'a> System<'a> for TriggerSystem {
         // Remove all entity movement markers.
         entity_moved.clear();
     }
-}
\ No newline at end of file
+}
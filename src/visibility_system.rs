@@ -1,7 +1,9 @@
 use specs::prelude::*;
 use super::{
     Viewshed, Position, Map, Player, Hidden, gamelog::GameLog, Name,
-    BlocksVisibility,
+    BlocksVisibility, Attributes, perception_check, perception_system::PASSIVE_DC,
+    lighting_system::{is_lit, NIGHT_VISION}, cloud_info, EntryTrigger, Skills, trap_spotting_bonus, Skill, improve_skills,
+    StatusEffects, StatusEffect,
 };
 use rltk::{field_of_view, Point};
 
@@ -19,12 +21,16 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteExpect<'a, GameLog>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, BlocksVisibility>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, EntryTrigger>,
+        WriteStorage<'a, Skills>,
+        ReadStorage<'a, StatusEffects>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut map, entities, mut viewshed, pos, player, mut hidden, mut rng,
-            mut log, names, blocks_visibility
+            mut log, names, blocks_visibility, attributes, traps, mut skills, status_effects,
         ) = data;
 
         map.view_blocked.clear();
@@ -32,16 +38,32 @@ impl<'a> System<'a> for VisibilitySystem {
             let idx = map.xy_idx(block_pos.x, block_pos.y);
             map.view_blocked.insert(idx);
         }
+        let thick_clouds: Vec<usize> = map.clouds.iter()
+            .filter(|(_idx, cloud)| cloud_info(&cloud.name).is_some_and(|info| info.blocks_visibility))
+            .map(|(idx, _cloud)| *idx)
+            .collect();
+        map.view_blocked.extend(thick_clouds);
 
         for (ent, viewshed, pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
                 viewshed.dirty = false;
                 viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), viewshed.range, &*map);
                 viewshed.visible_tiles.retain(|p| p.x >= 0 && p.x < map.width && p.y >= 0 && p.y < map.height );
+                // The blind can only feel their way around what's right next to them
+                if status_effects.get(ent).is_some_and(|s| s.has(StatusEffect::Blindness)) {
+                    let origin = Point::new(pos.x, pos.y);
+                    viewshed.visible_tiles.retain(|p| rltk::DistanceAlg::Pythagoras.distance2d(origin, *p) < 1.5);
+                }
 
                 // If this is the player, reveal what they can see
                 let _p: Option<&Player> = player.get(ent);
                 if let Some(_p) = _p {
+                    // You can only see what's lit, or right next to you
+                    let origin = Point::new(pos.x, pos.y);
+                    viewshed.visible_tiles.retain(|p| {
+                        is_lit(&map, map.xy_idx(p.x, p.y)) || rltk::DistanceAlg::Pythagoras.distance2d(origin, *p) <= NIGHT_VISION
+                    });
+
                     for t in map.visible_tiles.iter_mut() { *t = false }; // Clear the list of visible tiles.
                     for vis in viewshed.visible_tiles.iter() {
                         let idx = map.xy_idx(vis.x, vis.y);
@@ -53,13 +75,21 @@ impl<'a> System<'a> for VisibilitySystem {
                             // Iterate through each entity on each tile
                             let maybe_hidden = hidden.get(*e);
                             if let Some(_maybe_hidden) = maybe_hidden {
-                                // If the entity is hidden, roll to see if it gets revealed.
-                                if rng.roll_dice(1, 24) == 1 {
+                                // If the entity is hidden, the player might notice it.
+                                let noticed = match attributes.get(ent) {
+                                    None => rng.roll_dice(1, 24) == 1,
+                                    Some(attr) => {
+                                        let bonus = trap_spotting_bonus(traps.get(*e).is_some(), skills.get(ent));
+                                        perception_check(&mut rng, attr, skills.get(ent), PASSIVE_DC - bonus)
+                                    }
+                                };
+                                if noticed {
                                     let name = names.get(*e);
                                     if let Some(name) = name {
                                         log.entries.push(format!("You spotted a {}.", &name.name));
                                     }
                                     hidden.remove(*e);
+                                    improve_skills(&[(ent, Skill::Perception)], &mut skills, ent, &mut log);
                                 }
                             }
                         }